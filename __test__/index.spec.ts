@@ -1,5 +1,5 @@
 import test from 'ava'
-import { optimize } from '..'
+import { optimize, optimizeAsync, optimizeMany, visitSkip, type Output } from '..'
 
 test('basic', (t) => {
   const svg = `
@@ -8,5 +8,175 @@ test('basic', (t) => {
 </svg>
 `
   const output = optimize(svg)
-  t.snapshot(output)
+  t.is(
+    output.data,
+    '<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20" viewBox="0 0 20 20"><rect width="20" height="20" fill="rgba(255,255,255,.85)" rx="20"/></svg>',
+  )
+})
+
+test('js2svg', (t) => {
+  const svg = '<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>'
+  const output = optimize(svg, { js2svg: { pretty: true, indent: 2 } })
+  t.is(output.data, '<svg xmlns="http://www.w3.org/2000/svg">\n  <rect/>\n</svg>\n')
+})
+
+test('config', (t) => {
+  const svg = '<svg xmlns="http://www.w3.org/2000/svg" width="1.23456"><g><ellipse rx="5" ry="5"/></g></svg>'
+  const output = optimize(svg, {
+    floatPrecision: 1,
+    plugins: [
+      {
+        name: 'preset-default',
+        params: {
+          overrides: {
+            collapseGroups: false,
+          },
+        },
+      },
+    ],
+  })
+  t.is(output.data, '<svg xmlns="http://www.w3.org/2000/svg" width="1.2"><g><circle r="5"/></g></svg>')
+
+  t.throws(() => optimize('not svg', { path: 'logo.svg' }), { message: /^logo\.svg:1:1: / })
+  t.throws(() => optimize(svg, { plugins: ['removeFoo'] }), { message: 'Unknown plugin "removeFoo"' })
+})
+
+test('datauri', (t) => {
+  const svg = '<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>'
+  t.is(optimize(svg, { datauri: 'unenc' }).data, 'data:image/svg+xml,<svg xmlns="http://www.w3.org/2000/svg"><rect/></svg>')
+  t.is(
+    optimize(svg, { datauri: 'enc' }).data,
+    'data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22%3E%3Crect/%3E%3C/svg%3E',
+  )
+  t.regex(optimize(svg, { datauri: 'base64' }).data, /^data:image\/svg\+xml;base64,/)
+})
+
+test('custom plugin', (t) => {
+  const svg = '<svg xmlns="http://www.w3.org/2000/svg"><g><ellipse rx="5" ry="5"/><rect/></g></svg>'
+  const entered: string[] = []
+  const output = optimize(svg, {
+    plugins: [
+      'convertEllipseToCircle',
+      {
+        name: 'removeElements',
+        params: { name: 'rect' },
+        fn: (_root, params) => ({
+          element: {
+            enter: (node, parentNode) => {
+              entered.push(node.name)
+              if (node.name === params.name) {
+                parentNode.children = parentNode.children.filter((child) => child !== node)
+              }
+            },
+          },
+        }),
+      },
+      'collapseGroups',
+    ],
+  })
+  t.is(output.data, '<svg xmlns="http://www.w3.org/2000/svg"><circle r="5"/></svg>')
+  t.deepEqual(entered, ['svg', 'g', 'circle', 'rect'])
+
+  const visited: string[] = []
+  optimize(svg, {
+    plugins: [
+      {
+        name: 'skipGroups',
+        fn: () => ({
+          element: {
+            enter: (node) => {
+              visited.push(node.name)
+              return node.name === 'g' ? visitSkip : undefined
+            },
+            exit: (node) => void visited.push(`/${node.name}`),
+          },
+        }),
+      },
+    ],
+  })
+  t.deepEqual(visited, ['svg', 'g', '/svg'])
+
+  const received: unknown[] = []
+  optimize(svg, { plugins: [{ name: 'noParams', fn: (_root, params) => void received.push(params) }] })
+  t.deepEqual(received, [{}])
+
+  const ids = '<svg xmlns="http://www.w3.org/2000/svg">\n  <rect id="a" hidden/>\n  <circle id="a"/>\n</svg>'
+  const kept = optimize(ids, { plugins: [{ name: 'noop', fn: () => ({}) }, 'cleanupIds'] })
+  t.true(kept.data.includes('<rect hidden/>'))
+  t.like(kept.warnings.at(-1), { plugin: 'cleanupIds', line: 3, column: 3 })
+
+  const mark = (_root: unknown, params: { name: string }) => ({
+    element: {
+      enter: (node: { name: string; attributes: Record<string, string> }) => {
+        if (node.name === params.name) {
+          node.attributes.id = params.name
+        }
+      },
+    },
+  })
+  const twice = optimize(svg, {
+    stats: true,
+    plugins: [
+      { name: 'mark', params: { name: 'ellipse' }, fn: mark },
+      { name: 'mark', params: { name: 'rect' }, fn: mark },
+    ],
+  })
+  t.is(
+    twice.data,
+    '<svg xmlns="http://www.w3.org/2000/svg"><g><ellipse rx="5" ry="5" id="ellipse"/><rect id="rect"/></g></svg>',
+  )
+  t.deepEqual(twice.plugins.map(({ name }) => name), ['mark', 'mark'])
+
+  const fn = () => ({
+    element: {
+      enter: () => {
+        throw new Error('Plugin failed')
+      },
+    },
+  })
+  t.throws(() => optimize(svg, { plugins: [{ name: 'fail', fn }] }), { message: 'Plugin failed' })
+  t.throws(() => optimize(svg, { plugins: [{ name: 'convertColors', fn }] }), {
+    message: 'A plugin named "convertColors" is already registered',
+  })
+  t.throws(() => optimizeAsync(svg, { plugins: [{ name: 'fail', fn }] }), {
+    message: 'Custom plugins are only supported by `optimize`',
+  })
+})
+
+test('stats', (t) => {
+  const svg = '<svg xmlns="http://www.w3.org/2000/svg"><style>.a{}</style><g><rect id="a" class="a"/></g></svg>'
+  const output = optimize(svg, { stats: true, plugins: ['cleanupIds', 'collapseGroups'] })
+  t.is(output.originalSize, svg.length)
+  t.is(output.optimizedSize, output.data.length)
+  t.deepEqual(
+    output.plugins.map(({ name, savedBytes }) => [name, savedBytes]),
+    [
+      ['cleanupIds', 0],
+      ['collapseGroups', 7],
+    ],
+  )
+  t.is(output.warnings.length, 1)
+  t.is(output.warnings[0].plugin, 'cleanupIds')
+})
+
+test('optimizeAsync', async (t) => {
+  const svg = '<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>'
+  // the timings of the plugins differ from a run to another
+  const withoutTimes = (output: Output) => ({
+    ...output,
+    plugins: output.plugins.map(({ name, savedBytes }) => ({ name, savedBytes })),
+  })
+  const output = await optimizeAsync(svg, { js2svg: { pretty: true, indent: 2 } })
+  t.deepEqual(withoutTimes(output), withoutTimes(optimize(svg, { js2svg: { pretty: true, indent: 2 } })))
+  await t.throwsAsync(optimizeAsync('not svg'))
+})
+
+test('optimizeMany', async (t) => {
+  const results = await optimizeMany([
+    { data: '<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>' },
+    { path: 'not-found.svg' },
+  ])
+  t.is(results.length, 2)
+  t.is(results[0].output?.data, '<svg xmlns="http://www.w3.org/2000/svg"><rect/></svg>')
+  t.truthy(results[1].error)
 })
@@ -0,0 +1,160 @@
+use std::{cell::RefCell, rc::Rc};
+
+use napi::{Env, Error, JsFunction, JsObject, JsString, JsUnknown, NapiRaw, NapiValue, Result, ValueType};
+use serde_json::Value;
+use svgo_rs::{Context, Plugin};
+use swc_xml_ast::Document;
+
+use crate::xast;
+
+/// Returned by an `enter` callback of a custom plugin to skip the children of the node, and its `exit` callback.
+#[allow(non_upper_case_globals)]
+#[napi]
+pub const visitSkip: &str = "svgo-rs:visitSkip";
+
+/// The first error thrown by a JavaScript plugin, shared by all the plugins of an `optimize` call.
+pub type ErrorSlot = Rc<RefCell<Option<Error>>>;
+
+/// A custom plugin written in JavaScript, whose `fn` returns an SVGO visitor.
+///
+/// It can only run on the JavaScript thread, so it is registered in a `LocalRegistry`.
+///
+/// @example
+/// {
+///   name: 'removeRects',
+///   fn: () => ({
+///     element: {
+///       enter: (node, parentNode) => {
+///         if (node.name === 'rect') {
+///           parentNode.children = parentNode.children.filter((child) => child !== node)
+///         }
+///       }
+///     }
+///   })
+/// }
+pub struct JsPlugin {
+    env: Env,
+    name: String,
+    func: JsFunction,
+    error: ErrorSlot,
+}
+
+impl JsPlugin {
+    pub fn new(env: Env, name: String, func: JsFunction, error: ErrorSlot) -> Self {
+        Self {
+            env,
+            name,
+            func,
+            error,
+        }
+    }
+
+    fn run(&self, doc: &mut Document, params: &Value, context: &Context) -> Result<()> {
+        let env = &self.env;
+        let (root, origins) = xast::to_js(env, doc)?;
+        // like SVGO, the plugins without params get an empty object
+        let params = match params {
+            Value::Null => env.create_object()?.into_unknown(),
+            params => env.to_js_value(params)?,
+        };
+        let mut info = env.create_object()?;
+        if let Some(path) = &context.path {
+            info.set_named_property("path", path.as_str())?;
+        }
+        info.set_named_property("multipassCount", context.multipass_count)?;
+        let visitor = self.func.call(None, &[unknown(env, &root), params, info.into_unknown()])?;
+
+        if visitor.get_type()? == ValueType::Object {
+            // SAFETY: the type was checked.
+            let visitor: JsObject = unsafe { visitor.cast() };
+            visit(env, &root, &visitor, None)?;
+        }
+        *doc = xast::from_js(&root, &origins)?;
+        Ok(())
+    }
+}
+
+impl Plugin for JsPlugin {
+    type Params = Value;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "custom JavaScript plugin"
+    }
+
+    fn apply(&self, doc: &mut Document, params: &Value, context: &mut Context) {
+        if self.error.borrow().is_some() {
+            return;
+        }
+        if let Err(error) = self.run(doc, params, context) {
+            *self.error.borrow_mut() = Some(error);
+        }
+    }
+}
+
+fn unknown<V: NapiRaw>(env: &Env, value: V) -> JsUnknown {
+    // SAFETY: the value is a live handle of the same env.
+    unsafe { JsUnknown::from_raw_unchecked(env.raw(), value.raw()) }
+}
+
+fn includes(env: &Env, array: &JsObject, value: &JsObject) -> Result<bool> {
+    for index in 0..array.get_array_length()? {
+        if env.strict_equals(array.get_element::<JsUnknown>(index)?, value)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Walk the tree like `visit` of SVGO, calling the `enter` and `exit` callbacks of the node types.
+/// The children of a node are skipped when its `enter` callback returns `visitSkip`.
+fn visit(env: &Env, node: &JsObject, visitor: &JsObject, parent: Option<&JsObject>) -> Result<()> {
+    let node_type: String = node.get_named_property("type")?;
+    let callbacks: Option<JsObject> = visitor.get(&node_type)?;
+    let args = || -> Result<[JsUnknown; 2]> {
+        let parent = match parent {
+            Some(parent) => unknown(env, parent),
+            None => env.get_undefined()?.into_unknown(),
+        };
+        Ok([unknown(env, node), parent])
+    };
+
+    if let Some(callbacks) = &callbacks {
+        if let Some(enter) = callbacks.get::<_, JsFunction>("enter")? {
+            let result = enter.call(Some(callbacks), &args()?)?;
+            if result.get_type()? == ValueType::String {
+                // SAFETY: the type was checked.
+                let result: JsString = unsafe { result.cast() };
+                if result.into_utf8()?.as_str()? == visitSkip {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    let visit_children = match (node_type.as_str(), parent) {
+        ("root", _) => true,
+        // the element may have been removed by its `enter` callback
+        ("element", Some(parent)) => includes(env, &parent.get_named_property("children")?, node)?,
+        _ => false,
+    };
+    if visit_children {
+        let children: JsObject = node.get_named_property("children")?;
+        let mut index = 0;
+        // the length is read again as the callbacks may add children
+        while index < children.get_array_length()? {
+            visit(env, &children.get_element(index)?, visitor, Some(node))?;
+            index += 1;
+        }
+    }
+
+    if let Some(callbacks) = &callbacks {
+        if let Some(exit) = callbacks.get::<_, JsFunction>("exit")? {
+            exit.call(Some(callbacks), &args()?)?;
+        }
+    }
+    Ok(())
+}
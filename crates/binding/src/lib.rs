@@ -3,10 +3,374 @@
 #[macro_use]
 extern crate napi_derive;
 
-use svgo_rs::{optimize as optimize_core, Output};
+mod js_plugin;
+mod xast;
+
+use std::path::PathBuf;
+
+use js_plugin::{ErrorSlot, JsPlugin};
+use napi::{
+    bindgen_prelude::{AsyncTask, Either},
+    Env, Error, JsFunction, Status, Task,
+};
+use serde_json::Value;
+use svgo_rs::{
+    optimize as optimize_core, optimize_many as optimize_many_core, optimize_with_local_registry, DataUri, Eol,
+    LocalRegistry, PluginConfig, Registry,
+};
+
+/// A problem which did not stop the optimization.
+#[napi(object)]
+pub struct Warning {
+    pub message: String,
+    /// The plugin reporting the problem, none for the parser.
+    pub plugin: Option<String>,
+    /// Path of the SVG file, from the `path` of the config.
+    pub path: Option<String>,
+    /// 1-based line of the problem, if known.
+    pub line: Option<u32>,
+    /// 1-based column of the problem, if known.
+    pub column: Option<u32>,
+    /// The source line of the problem with a caret under the column, if known.
+    pub snippet: Option<String>,
+}
+
+impl From<svgo_rs::Warning> for Warning {
+    fn from(warning: svgo_rs::Warning) -> Self {
+        Self {
+            message: warning.message,
+            plugin: warning.plugin,
+            path: warning.path,
+            line: warning.line,
+            column: warning.column,
+            snippet: warning.snippet,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct Output {
+    pub data: String,
+    /// Errors the parser recovered from and problems reported by the plugins.
+    pub warnings: Vec<Warning>,
+    /// How many times the plugins were run.
+    pub passes: u32,
+    /// Size of the input in bytes.
+    pub original_size: u32,
+    /// Size of `data` in bytes.
+    pub optimized_size: u32,
+    /// Stats of every plugin, in the order they ran.
+    pub plugins: Vec<PluginStats>,
+}
+
+impl From<svgo_rs::Output> for Output {
+    fn from(output: svgo_rs::Output) -> Self {
+        Self {
+            data: output.data,
+            warnings: output.warnings.into_iter().map(Into::into).collect(),
+            passes: output.passes,
+            original_size: output.original_size,
+            optimized_size: output.optimized_size,
+            plugins: output.plugins.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// What a plugin did, summed over all the passes.
+#[napi(object)]
+pub struct PluginStats {
+    pub name: String,
+    /// Time spent in the plugin, in milliseconds.
+    pub time: f64,
+    /// How many bytes the plugin removed from the output, negative when it added some.
+    ///
+    /// Only measured with the `stats` option, as it stringifies the document after every plugin.
+    pub saved_bytes: Option<i64>,
+}
+
+impl From<svgo_rs::PluginStats> for PluginStats {
+    fn from(stats: svgo_rs::PluginStats) -> Self {
+        Self {
+            name: stats.name,
+            time: stats.time,
+            saved_bytes: stats.saved_bytes,
+        }
+    }
+}
+
+/// Options of the output.
+#[napi(object)]
+pub struct Js2SvgOptions {
+    pub indent: Option<u32>,
+    pub pretty: Option<bool>,
+    pub use_short_tags: Option<bool>,
+    #[napi(ts_type = "'lf' | 'crlf'")]
+    pub eol: Option<String>,
+    pub final_newline: Option<bool>,
+}
+
+impl TryFrom<Js2SvgOptions> for svgo_rs::Js2SvgOptions {
+    type Error = Error;
+
+    fn try_from(options: Js2SvgOptions) -> napi::Result<Self> {
+        let defaults = svgo_rs::Js2SvgOptions::default();
+        let eol = match options.eol.as_deref() {
+            None => defaults.eol,
+            Some("lf") => Eol::Lf,
+            Some("crlf") => Eol::Crlf,
+            Some(eol) => return Err(Error::new(Status::InvalidArg, format!("Unknown eol \"{}\"", eol))),
+        };
+        Ok(Self {
+            indent: options.indent.map_or(defaults.indent, |indent| indent as usize),
+            pretty: options.pretty.unwrap_or(defaults.pretty),
+            use_short_tags: options.use_short_tags.unwrap_or(defaults.use_short_tags),
+            eol,
+            final_newline: options.final_newline.unwrap_or(defaults.final_newline),
+        })
+    }
+}
+
+/// How the parser treats whitespace.
+#[napi(object)]
+pub struct ParseOptions {
+    /// Keep the text in `xml:space="preserve"` subtrees, `true` by default.
+    pub preserve_xml_space: Option<bool>,
+    /// Keep the whitespace of `<style>`, `<script>` and `<foreignObject>`.
+    pub preserve_raw_text: Option<bool>,
+    /// Keep the comments as is instead of trimming them.
+    pub verbatim_comments: Option<bool>,
+}
+
+impl From<ParseOptions> for svgo_rs::ParseOptions {
+    fn from(options: ParseOptions) -> Self {
+        let defaults = svgo_rs::ParseOptions::default();
+        Self {
+            preserve_xml_space: options.preserve_xml_space.unwrap_or(defaults.preserve_xml_space),
+            preserve_raw_text: options.preserve_raw_text.unwrap_or(defaults.preserve_raw_text),
+            verbatim_comments: options.verbatim_comments.unwrap_or(defaults.verbatim_comments),
+        }
+    }
+}
+
+/// A plugin of the config with its params, e.g. `{ name: 'preset-default', params: { overrides: {} } }`.
+#[napi(object, object_to_js = false)]
+pub struct PluginItem {
+    pub name: String,
+    #[napi(ts_type = "Record<string, any>")]
+    pub params: Option<Value>,
+    /// A custom plugin returning an SVGO visitor, only supported by `optimize`.
+    #[napi(js_name = "fn", ts_type = "(root: any, params: any, info: any) => any")]
+    pub func: Option<JsFunction>,
+}
+
+/// The SVGO config.
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct Config {
+    /// Path of the SVG file, used in error messages.
+    pub path: Option<String>,
+    /// Rerun the plugins until the output stops shrinking.
+    pub multipass: Option<bool>,
+    /// Measure the bytes saved by every plugin, stringifying the document after each of them.
+    pub stats: Option<bool>,
+    /// Number of decimals kept by every plugin rounding numbers, unless its params set it.
+    pub float_precision: Option<i32>,
+    pub parser: Option<ParseOptions>,
+    #[napi(js_name = "js2svg")]
+    pub js2svg: Option<Js2SvgOptions>,
+    /// Output a data URI instead of the SVG.
+    #[napi(ts_type = "'base64' | 'enc' | 'unenc'")]
+    pub datauri: Option<String>,
+    /// Plugins to run, in order, `preset-default` by default.
+    pub plugins: Option<Vec<Either<String, PluginItem>>>,
+}
+
+impl TryFrom<Config> for svgo_rs::Config {
+    type Error = Error;
+
+    fn try_from(config: Config) -> napi::Result<Self> {
+        let defaults = svgo_rs::Config::default();
+        let plugins = match config.plugins {
+            Some(plugins) => plugins
+                .into_iter()
+                .map(|plugin| match plugin {
+                    Either::A(name) => PluginConfig::Name(name),
+                    Either::B(PluginItem { name, params, .. }) => PluginConfig::WithParams { name, params },
+                })
+                .collect(),
+            None => defaults.plugins,
+        };
+        Ok(Self {
+            path: config.path,
+            plugins,
+            multipass: config.multipass.unwrap_or(defaults.multipass),
+            stats: config.stats.unwrap_or(defaults.stats),
+            float_precision: config.float_precision,
+            parser: config.parser.map_or(defaults.parser, Into::into),
+            js2svg: match config.js2svg {
+                Some(js2svg) => js2svg.try_into()?,
+                None => defaults.js2svg,
+            },
+            datauri: match config.datauri.as_deref() {
+                None => None,
+                Some("base64") => Some(DataUri::Base64),
+                Some("enc") => Some(DataUri::Enc),
+                Some("unenc") => Some(DataUri::Unenc),
+                Some(datauri) => {
+                    return Err(Error::new(Status::InvalidArg, format!("Unknown datauri \"{}\"", datauri)))
+                },
+            },
+        })
+    }
+}
+
+impl Config {
+    /// Take the functions of the custom plugins out of the config, with their names.
+    ///
+    /// Like in SVGO, several custom plugins may have the same name, so their position in the config
+    /// replaces it as the key they are registered under.
+    fn take_js_plugins(&mut self) -> Vec<(String, String, JsFunction)> {
+        let mut js_plugins = vec![];
+        for (index, plugin) in self.plugins.iter_mut().flatten().enumerate() {
+            if let Either::B(item) = plugin {
+                if let Some(func) = item.func.take() {
+                    let key = format!("{}#{}", item.name, index);
+                    let name = std::mem::replace(&mut item.name, key.clone());
+                    js_plugins.push((key, name, func));
+                }
+            }
+        }
+        js_plugins
+    }
+}
+
+/// Convert the config of an API running off the JavaScript thread, where custom plugins can't be called.
+fn to_config(config: Option<Config>) -> napi::Result<svgo_rs::Config> {
+    let mut config = config.unwrap_or_default();
+    if !config.take_js_plugins().is_empty() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "Custom plugins are only supported by `optimize`".to_string(),
+        ));
+    }
+    config.try_into()
+}
 
 /// The core of SVGO RS
 #[napi]
-pub fn optimize(input: String) -> Output {
-    optimize_core(input)
+pub fn optimize(env: Env, input: String, config: Option<Config>) -> napi::Result<Output> {
+    let mut config = config.unwrap_or_default();
+    let error = ErrorSlot::default();
+    let registry = Registry::new();
+    let mut local_registry = LocalRegistry::new(&registry);
+    for (key, name, func) in config.take_js_plugins() {
+        local_registry
+            .register_as(key, JsPlugin::new(env, name, func, error.clone()))
+            .map_err(|err| Error::new(Status::InvalidArg, err.to_string()))?;
+    }
+    let config = config.try_into()?;
+
+    let output = optimize_with_local_registry(input, &config, &local_registry)
+        .map_err(|err| Error::from_reason(err.to_string()))?;
+    let error = error.borrow_mut().take();
+    match error {
+        Some(error) => Err(error),
+        None => Ok(output.into()),
+    }
+}
+
+pub struct OptimizeTask {
+    input: String,
+    config: svgo_rs::Config,
+}
+
+impl Task for OptimizeTask {
+    type Output = svgo_rs::Output;
+    type JsValue = Output;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let input = std::mem::take(&mut self.input);
+        optimize_core(input, &self.config)
+            .map_err(|err| Error::from_reason(err.to_string()))
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Like `optimize`, running on the libuv thread pool instead of blocking the event loop.
+#[napi]
+pub fn optimize_async(input: String, config: Option<Config>) -> napi::Result<AsyncTask<OptimizeTask>> {
+    let config = to_config(config)?;
+    Ok(AsyncTask::new(OptimizeTask { input, config }))
+}
+
+/// An SVG of `optimizeMany`, either its content or the path of its file.
+#[napi(object)]
+pub struct ManyInput {
+    pub data: Option<String>,
+    pub path: Option<String>,
+}
+
+impl TryFrom<ManyInput> for svgo_rs::Input {
+    type Error = Error;
+
+    fn try_from(input: ManyInput) -> napi::Result<Self> {
+        match (input.data, input.path) {
+            (Some(data), None) => Ok(svgo_rs::Input::Data(data)),
+            (None, Some(path)) => Ok(svgo_rs::Input::Path(PathBuf::from(path))),
+            _ => Err(Error::new(Status::InvalidArg, "Expected either \"data\" or \"path\"".to_string())),
+        }
+    }
+}
+
+/// The result of a single SVG of `optimizeMany`.
+#[napi(object)]
+pub struct ManyResult {
+    pub output: Option<Output>,
+    pub error: Option<String>,
+}
+
+pub struct OptimizeManyTask {
+    inputs: Vec<svgo_rs::Input>,
+    config: svgo_rs::Config,
+}
+
+impl Task for OptimizeManyTask {
+    type Output = Vec<Result<svgo_rs::Output, svgo_rs::Error>>;
+    type JsValue = Vec<ManyResult>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let inputs = std::mem::take(&mut self.inputs);
+        optimize_many_core(inputs, &self.config)
+            .map_err(|err| Error::from_reason(err.to_string()))
+    }
+
+    fn resolve(&mut self, _: Env, outputs: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(outputs
+            .into_iter()
+            .map(|result| match result {
+                Ok(output) => ManyResult {
+                    output: Some(output.into()),
+                    error: None,
+                },
+                Err(err) => ManyResult {
+                    output: None,
+                    error: Some(err.to_string()),
+                },
+            })
+            .collect())
+    }
+}
+
+/// Optimize many SVGs on a thread pool, resolving with the results in the order of the inputs.
+#[napi]
+pub fn optimize_many(inputs: Vec<ManyInput>, config: Option<Config>) -> napi::Result<AsyncTask<OptimizeManyTask>> {
+    let inputs = inputs
+        .into_iter()
+        .map(svgo_rs::Input::try_from)
+        .collect::<napi::Result<Vec<_>>>()?;
+    let config = to_config(config)?;
+    Ok(AsyncTask::new(OptimizeManyTask { inputs, config }))
 }
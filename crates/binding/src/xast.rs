@@ -0,0 +1,217 @@
+// Conversion between the document and the XAST tree of SVGO, as seen by JavaScript plugins.
+//
+// @example
+// {
+//   type: 'element',
+//   name: 'rect',
+//   attributes: { width: '10' },
+//   children: []
+// }
+
+use napi::{Env, Error, JsObject, JsString, JsUnknown, Property, PropertyAttributes, Result};
+use swc_core::common::{Span, DUMMY_SP};
+use swc_xml_ast::*;
+
+// the hidden property of the nodes pointing at their origin
+const ORIGIN: &str = "__origin";
+
+/// What the XAST tree leaves out of the nodes, restored when converting back so that the later
+/// warnings keep their locations and the valueless attributes stay valueless.
+#[derive(Debug, Default)]
+pub struct Origins {
+    spans: Vec<Span>,
+    // the attributes of the elements, by the index of their span
+    attributes: Vec<(usize, Vec<Attribute>)>,
+}
+
+impl Origins {
+    fn add(&mut self, env: &Env, node: &mut JsObject, span: Span) -> Result<usize> {
+        let index = self.spans.len();
+        self.spans.push(span);
+        let origin = env.create_uint32(index as u32)?;
+        // not enumerable, so that it is left out of the copies and the serialized nodes
+        node.define_properties(&[Property::new(ORIGIN)?
+            .with_value(&origin)
+            .with_property_attributes(PropertyAttributes::Default)])?;
+        Ok(index)
+    }
+
+    fn get(&self, node: &JsObject) -> Result<Option<usize>> {
+        let index: Option<u32> = node.get(ORIGIN)?;
+        Ok(index.map(|index| index as usize).filter(|index| *index < self.spans.len()))
+    }
+
+    fn span(&self, node: &JsObject) -> Result<Span> {
+        Ok(self.get(node)?.map_or(DUMMY_SP, |index| self.spans[index]))
+    }
+
+    fn attributes(&self, node: &JsObject) -> Result<&[Attribute]> {
+        let attributes = self.get(node)?.and_then(|index| {
+            let position = self.attributes.binary_search_by_key(&index, |(index, _)| *index).ok()?;
+            Some(&*self.attributes[position].1)
+        });
+        Ok(attributes.unwrap_or_default())
+    }
+}
+
+pub fn to_js(env: &Env, doc: &Document) -> Result<(JsObject, Origins)> {
+    let mut origins = Origins::default();
+    let mut root = env.create_object()?;
+    origins.add(env, &mut root, doc.span)?;
+    root.set_named_property("type", "root")?;
+    root.set_named_property("children", children_to_js(env, &doc.children, &mut origins)?)?;
+    Ok((root, origins))
+}
+
+fn children_to_js(env: &Env, children: &[Child], origins: &mut Origins) -> Result<JsObject> {
+    let mut array = env.create_array_with_length(children.len())?;
+    for (index, child) in children.iter().enumerate() {
+        array.set_element(index as u32, child_to_js(env, child, origins)?)?;
+    }
+    Ok(array)
+}
+
+fn child_span(child: &Child) -> Span {
+    match child {
+        Child::DocumentType(doctype) => doctype.span,
+        Child::Element(element) => element.span,
+        Child::Text(text) => text.span,
+        Child::CdataSection(cdata) => cdata.span,
+        Child::Comment(comment) => comment.span,
+        Child::ProcessingInstruction(instruction) => instruction.span,
+    }
+}
+
+fn child_to_js(env: &Env, child: &Child, origins: &mut Origins) -> Result<JsObject> {
+    let mut node = env.create_object()?;
+    let origin = origins.add(env, &mut node, child_span(child))?;
+    match child {
+        Child::DocumentType(doctype) => {
+            let mut data = env.create_object()?;
+            data.set_named_property("doctype", svgo_rs::doctype_declaration(doctype))?;
+            node.set_named_property("type", "doctype")?;
+            node.set_named_property("name", "svg")?;
+            node.set_named_property("data", data)?;
+        },
+        Child::Element(element) => {
+            let mut attributes = env.create_object()?;
+            for attr in element.attributes.iter() {
+                attributes.set_named_property(&attr.name, attr.value.as_deref().unwrap_or(""))?;
+            }
+            node.set_named_property("type", "element")?;
+            node.set_named_property("name", &*element.tag_name)?;
+            node.set_named_property("attributes", attributes)?;
+            origins.attributes.push((origin, element.attributes.clone()));
+            node.set_named_property("children", children_to_js(env, &element.children, origins)?)?;
+        },
+        Child::Text(text) => {
+            node.set_named_property("type", "text")?;
+            node.set_named_property("value", &*text.data)?;
+        },
+        Child::CdataSection(cdata) => {
+            node.set_named_property("type", "cdata")?;
+            node.set_named_property("value", &*cdata.data)?;
+        },
+        Child::Comment(comment) => {
+            node.set_named_property("type", "comment")?;
+            node.set_named_property("value", &*comment.data)?;
+        },
+        Child::ProcessingInstruction(instruction) => {
+            node.set_named_property("type", "instruction")?;
+            node.set_named_property("name", &*instruction.target)?;
+            node.set_named_property("value", &*instruction.data)?;
+        },
+    }
+    Ok(node)
+}
+
+pub fn from_js(root: &JsObject, origins: &Origins) -> Result<Document> {
+    Ok(Document {
+        span: origins.span(root)?,
+        children: children_from_js(root, origins)?,
+    })
+}
+
+fn children_from_js(node: &JsObject, origins: &Origins) -> Result<Vec<Child>> {
+    let array: JsObject = node.get_named_property("children")?;
+    let mut children = vec![];
+    for index in 0..array.get_array_length()? {
+        children.push(child_from_js(&array.get_element(index)?, origins)?);
+    }
+    Ok(children)
+}
+
+fn string(node: &JsObject, name: &str) -> Result<String> {
+    let value: JsUnknown = node.get_named_property(name)?;
+    value.coerce_to_string()?.into_utf8()?.into_owned()
+}
+
+fn child_from_js(node: &JsObject, origins: &Origins) -> Result<Child> {
+    let span = origins.span(node)?;
+    let child = match string(node, "type")?.as_str() {
+        "doctype" => {
+            let data: JsObject = node.get_named_property("data")?;
+            let declaration = string(&data, "doctype")?;
+            let doctype = svgo_rs::parse_doctype(&declaration)
+                .ok_or_else(|| Error::from_reason(format!("Invalid doctype \"{}\"", declaration)))?;
+            Child::DocumentType(DocumentType { span, ..doctype })
+        },
+        "element" => {
+            let attributes: JsObject = node.get_named_property("attributes")?;
+            let names = attributes.get_property_names()?;
+            let original = origins.attributes(node)?;
+            let mut attrs = vec![];
+            for index in 0..names.get_array_length()? {
+                let name = names.get_element::<JsString>(index)?.into_utf8()?.into_owned()?;
+                let value = string(&attributes, &name)?;
+                let attr = match original.iter().find(|attr| *attr.name == name) {
+                    // unchanged, possibly valueless
+                    Some(attr) if attr.value.as_deref().unwrap_or("") == value => attr.clone(),
+                    Some(attr) => Attribute {
+                        value: Some(value.into()),
+                        raw_value: None,
+                        ..attr.clone()
+                    },
+                    None => Attribute {
+                        span: DUMMY_SP,
+                        namespace: None,
+                        prefix: None,
+                        value: Some(value.into()),
+                        name: name.into(),
+                        raw_name: None,
+                        raw_value: None,
+                    },
+                };
+                attrs.push(attr);
+            }
+            Child::Element(Element {
+                span,
+                tag_name: string(node, "name")?.into(),
+                attributes: attrs,
+                children: children_from_js(node, origins)?,
+            })
+        },
+        "text" => Child::Text(Text {
+            span,
+            data: string(node, "value")?.into(),
+            raw: None,
+        }),
+        "cdata" => Child::CdataSection(CdataSection {
+            span,
+            data: string(node, "value")?.into(),
+            raw: None,
+        }),
+        "comment" => Child::Comment(Comment {
+            span,
+            data: string(node, "value")?.into(),
+            raw: None,
+        }),
+        "instruction" => Child::ProcessingInstruction(ProcessingInstruction {
+            span,
+            target: string(node, "name")?.into(),
+            data: string(node, "value")?.into(),
+        }),
+        node_type => return Err(Error::from_reason(format!("Unknown node type \"{}\"", node_type))),
+    };
+    Ok(child)
+}
@@ -0,0 +1,71 @@
+#ifndef SVGO_RS_H
+#define SVGO_RS_H
+
+/* Generated by cbindgen from crates/capi/src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of `svgo_optimize`, owned by the caller and freed with `svgo_result_free`.
+ */
+typedef struct SvgoResult {
+  /**
+   * The optimized SVG, null on error.
+   */
+  char *data;
+  /**
+   * The error message, null on success.
+   */
+  char *error;
+  /**
+   * The warnings as a JSON array, null on error.
+   */
+  char *warnings;
+  /**
+   * How many times the plugins were run.
+   */
+  uint32_t passes;
+  /**
+   * Size of the input in bytes.
+   */
+  uint32_t original_size;
+  /**
+   * Size of `data` in bytes.
+   */
+  uint32_t optimized_size;
+} SvgoResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Optimize an SVG.
+ *
+ * `config_json` is a `svgo.config.json`-style config, or null for the default config.
+ * Never returns null, the result must be freed with `svgo_result_free`.
+ *
+ * # Safety
+ *
+ * `svg` and `config_json` must be null or point to nul-terminated strings.
+ */
+struct SvgoResult *svgo_optimize(const char *svg, const char *config_json);
+
+/**
+ * Free a result of `svgo_optimize`, doing nothing for null.
+ *
+ * # Safety
+ *
+ * `result` must be null or a result of `svgo_optimize` which was not freed yet.
+ */
+void svgo_result_free(struct SvgoResult *result);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* SVGO_RS_H */
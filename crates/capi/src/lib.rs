@@ -0,0 +1,184 @@
+#![deny(clippy::all)]
+
+//! C API of SVGO RS, see `include/svgo.h`.
+//!
+//! @example
+//! SvgoResult *result = svgo_optimize(svg, "{\"multipass\":true}");
+//! if (result->error) {
+//!   fprintf(stderr, "%s\n", result->error);
+//! } else {
+//!   puts(result->data);
+//! }
+//! svgo_result_free(result);
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic, ptr,
+};
+
+use svgo_rs::{optimize, Config, Output};
+
+/// The result of `svgo_optimize`, owned by the caller and freed with `svgo_result_free`.
+#[repr(C)]
+pub struct SvgoResult {
+    /// The optimized SVG, null on error.
+    pub data: *mut c_char,
+    /// The error message, null on success.
+    pub error: *mut c_char,
+    /// The warnings as a JSON array, null on error.
+    pub warnings: *mut c_char,
+    /// How many times the plugins were run.
+    pub passes: u32,
+    /// Size of the input in bytes.
+    pub original_size: u32,
+    /// Size of `data` in bytes.
+    pub optimized_size: u32,
+}
+
+impl SvgoResult {
+    fn ok(output: Output) -> Result<Self, String> {
+        let warnings = serde_json::to_string(&output.warnings).map_err(|err| err.to_string())?;
+        Ok(Self {
+            data: into_raw(output.data)?,
+            error: ptr::null_mut(),
+            warnings: into_raw(warnings)?,
+            passes: output.passes,
+            original_size: output.original_size,
+            optimized_size: output.optimized_size,
+        })
+    }
+
+    fn err(message: String) -> Self {
+        Self {
+            data: ptr::null_mut(),
+            // the messages may quote a JSON string of the config with an escaped nul character
+            error: CString::new(message.replace('\0', "\\0")).unwrap().into_raw(),
+            warnings: ptr::null_mut(),
+            passes: 0,
+            original_size: 0,
+            optimized_size: 0,
+        }
+    }
+}
+
+fn into_raw(string: String) -> Result<*mut c_char, String> {
+    CString::new(string)
+        .map(CString::into_raw)
+        .map_err(|_| "The output contains a nul character".to_string())
+}
+
+/// Read a nul-terminated UTF-8 string of the caller.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a nul-terminated string.
+unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>, String> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(Some)
+        .map_err(|_| format!("`{}` is not valid UTF-8", name))
+}
+
+unsafe fn run(svg: *const c_char, config_json: *const c_char) -> Result<SvgoResult, String> {
+    let svg = read_str(svg, "svg")?.ok_or_else(|| "`svg` is null".to_string())?;
+    let config = match read_str(config_json, "config_json")? {
+        Some(json) => Config::from_json(json).map_err(|err| err.to_string())?,
+        None => Config::default(),
+    };
+    let output = optimize(svg.to_string(), &config).map_err(|err| err.to_string())?;
+    SvgoResult::ok(output)
+}
+
+/// Optimize an SVG.
+///
+/// `config_json` is a `svgo.config.json`-style config, or null for the default config.
+/// Never returns null, the result must be freed with `svgo_result_free`.
+///
+/// # Safety
+///
+/// `svg` and `config_json` must be null or point to nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn svgo_optimize(svg: *const c_char, config_json: *const c_char) -> *mut SvgoResult {
+    let result = panic::catch_unwind(|| run(svg, config_json)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format!("svgo-rs panicked: {}", message))
+    });
+    Box::into_raw(Box::new(result.unwrap_or_else(SvgoResult::err)))
+}
+
+/// Free a result of `svgo_optimize`, doing nothing for null.
+///
+/// # Safety
+///
+/// `result` must be null or a result of `svgo_optimize` which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn svgo_result_free(result: *mut SvgoResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = Box::from_raw(result);
+    for string in [result.data, result.error, result.warnings] {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn string(ptr: *const c_char) -> Option<String> {
+        (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn optimize_svg() {
+        let svg = CString::new("<svg xmlns=\"http://www.w3.org/2000/svg\"><g class=\" a  b \"/></svg>").unwrap();
+        let config = CString::new(r#"{ "plugins": ["cleanupAttrs"] }"#).unwrap();
+        unsafe {
+            let result = svgo_optimize(svg.as_ptr(), config.as_ptr());
+            assert_eq!(string((*result).error), None);
+            assert_eq!(
+                string((*result).data).unwrap(),
+                "<svg xmlns=\"http://www.w3.org/2000/svg\"><g class=\"a b\"/></svg>"
+            );
+            assert_eq!(string((*result).warnings).unwrap(), "[]");
+            assert_eq!((*result).passes, 1);
+            svgo_result_free(result);
+
+            let result = svgo_optimize(svg.as_ptr(), ptr::null());
+            assert_eq!(string((*result).error), None);
+            svgo_result_free(result);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let svg = CString::new("<svg/>").unwrap();
+        let config = CString::new(r#"{ "plugins": ["unknown"] }"#).unwrap();
+        unsafe {
+            let result = svgo_optimize(svg.as_ptr(), config.as_ptr());
+            assert!((*result).data.is_null());
+            assert!(string((*result).error).unwrap().contains("unknown"));
+            svgo_result_free(result);
+
+            let config = CString::new(r#"{ "plugins": ["unknown\u0000"] }"#).unwrap();
+            let result = svgo_optimize(svg.as_ptr(), config.as_ptr());
+            assert!(string((*result).error).unwrap().contains("unknown\\0"));
+            svgo_result_free(result);
+
+            let result = svgo_optimize(ptr::null(), ptr::null());
+            assert_eq!(string((*result).error).unwrap(), "`svg` is null");
+            svgo_result_free(result);
+        }
+        unsafe { svgo_result_free(ptr::null_mut()) };
+    }
+}
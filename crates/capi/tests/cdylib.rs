@@ -0,0 +1,62 @@
+#![cfg(unix)]
+
+//! Load the built shared library like a C program would, it must not need any symbol of Node.
+
+use std::{
+    env::{self, consts},
+    ffi::{c_char, c_void, CStr, CString},
+    path::PathBuf,
+};
+
+#[repr(C)]
+struct SvgoResult {
+    data: *mut c_char,
+    error: *mut c_char,
+    warnings: *mut c_char,
+    passes: u32,
+    original_size: u32,
+    optimized_size: u32,
+}
+
+type Optimize = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut SvgoResult;
+type Free = unsafe extern "C" fn(*mut SvgoResult);
+
+// the tests are in `target/<profile>/deps`, next to the library of their crate
+fn library_path() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let dir = exe.parent().unwrap().parent().unwrap();
+    dir.join(format!("{}svgo{}", consts::DLL_PREFIX, consts::DLL_SUFFIX))
+}
+
+unsafe fn symbol(library: *mut c_void, name: &str) -> *mut c_void {
+    let name = CString::new(name).unwrap();
+    let symbol = libc::dlsym(library, name.as_ptr());
+    assert!(!symbol.is_null(), "missing symbol {:?}", name);
+    symbol
+}
+
+#[test]
+fn load_library() {
+    let path = CString::new(library_path().to_str().unwrap()).unwrap();
+    unsafe {
+        // resolve all the symbols now, an undefined one fails the loading
+        let library = libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+        assert!(
+            !library.is_null(),
+            "{}",
+            CStr::from_ptr(libc::dlerror()).to_string_lossy()
+        );
+        let optimize: Optimize = std::mem::transmute(symbol(library, "svgo_optimize"));
+        let free: Free = std::mem::transmute(symbol(library, "svgo_result_free"));
+
+        let svg = CString::new("<svg xmlns=\"http://www.w3.org/2000/svg\"><g><rect/></g></svg>").unwrap();
+        let result = optimize(svg.as_ptr(), std::ptr::null());
+        assert!((*result).error.is_null());
+        assert_eq!(
+            CStr::from_ptr((*result).data).to_str().unwrap(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"><rect/></svg>"
+        );
+        free(result);
+        libc::dlclose(library);
+    }
+}
@@ -0,0 +1,20 @@
+use std::{fs, path::Path};
+
+#[test]
+fn header_is_up_to_date() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    let mut header = vec![];
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("unable to generate the C header")
+        .write(&mut header);
+
+    let committed = fs::read_to_string(crate_dir.join("include/svgo.h")).unwrap();
+    assert!(
+        String::from_utf8(header).unwrap() == committed,
+        "include/svgo.h is out of date, run `cbindgen --config cbindgen.toml --output include/svgo.h` in crates/capi"
+    );
+}
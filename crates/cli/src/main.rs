@@ -0,0 +1,290 @@
+#![deny(clippy::all)]
+
+use std::{
+    fs,
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Instant,
+};
+
+use clap::{Parser, ValueEnum};
+use svgo_rs::{optimize, Config, DataUri, Eol, Output};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum EolArg {
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DataUriArg {
+    Base64,
+    Enc,
+    Unenc,
+}
+
+/// SVGO RS command line interface
+#[derive(Debug, Parser)]
+#[command(name = "svgo-rs", version, about = "Speedy SVGO rewritten in Rust")]
+struct Cli {
+    /// Input files, "-" for STDIN
+    #[arg(value_name = "INPUT")]
+    args: Vec<PathBuf>,
+    /// Input files, "-" for STDIN
+    #[arg(short, long, num_args = 1..)]
+    input: Vec<PathBuf>,
+    /// Input SVG data string
+    #[arg(short, long)]
+    string: Option<String>,
+    /// Input folder, optimize and rewrite all *.svg files
+    #[arg(short, long)]
+    folder: Option<PathBuf>,
+    /// Output files or folder (by default the same as the input), "-" for STDOUT
+    #[arg(short, long, num_args = 1..)]
+    output: Vec<PathBuf>,
+    /// Set number of digits in the fractional part
+    #[arg(short, long)]
+    precision: Option<i32>,
+    /// Output as Data URI string
+    #[arg(long, value_enum)]
+    datauri: Option<DataUriArg>,
+    /// Custom config file, by default svgo.config.json in the current directory if it exists
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Pass over SVGs multiple times to ensure all optimizations are applied
+    #[arg(long)]
+    multipass: bool,
+    /// Make SVG pretty printed
+    #[arg(long)]
+    pretty: bool,
+    /// Indent number when pretty printing SVGs
+    #[arg(long)]
+    indent: Option<usize>,
+    /// Line break to use when outputting SVG
+    #[arg(long, value_enum)]
+    eol: Option<EolArg>,
+    /// Ensure SVG ends with a line break
+    #[arg(long)]
+    final_newline: bool,
+    /// Use with '--folder'. Optimizes *.svg files in folders recursively
+    #[arg(short, long)]
+    recursive: bool,
+    /// Only output error messages, not regular status messages
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+type CliResult<T> = Result<T, String>;
+
+const DEFAULT_CONFIG_FILE: &str = "svgo.config.json";
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> CliResult<()> {
+    let config = load_config(cli)?;
+
+    if let Some(string) = &cli.string {
+        let output = cli.output.first().map(PathBuf::as_path);
+        return process(cli, &config, string.clone(), None, output);
+    }
+
+    if let Some(folder) = &cli.folder {
+        let output_folder = cli.output.first().unwrap_or(folder);
+        let mut files = vec![];
+        collect_svg_files(folder, cli.recursive, &mut files)
+            .map_err(|err| format!("{}: {}", folder.display(), err))?;
+        for file in files {
+            let output = output_folder.join(file.strip_prefix(folder).unwrap());
+            process_file(cli, &config, &file, Some(&output))?;
+        }
+        return Ok(());
+    }
+
+    let inputs: Vec<&PathBuf> = cli.input.iter().chain(cli.args.iter()).collect();
+    if inputs.is_empty() {
+        if io::stdin().is_terminal() {
+            return Err("no input, see --help".to_string());
+        }
+        let output = cli.output.first().map(PathBuf::as_path).unwrap_or(Path::new("-"));
+        return process_file(cli, &config, Path::new("-"), Some(output));
+    }
+
+    for (index, input) in inputs.iter().enumerate() {
+        let output = match cli.output.as_slice() {
+            [] => None,
+            [output] if inputs.len() > 1 || output.is_dir() => {
+                let file_name = input.file_name().ok_or_else(|| format!("{}: not a file", input.display()))?;
+                Some(output.join(file_name))
+            },
+            outputs if outputs.len() == inputs.len() => Some(outputs[index].clone()),
+            _ => return Err("the number of outputs does not match the number of inputs".to_string()),
+        };
+        process_file(cli, &config, input, output.as_deref())?;
+    }
+    Ok(())
+}
+
+fn load_config(cli: &Cli) -> CliResult<Config> {
+    let path = match &cli.config {
+        Some(path) => Some(path.as_path()),
+        None => Some(Path::new(DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()),
+    };
+    let mut config = match path {
+        Some(path) => Config::load(path).map_err(|err| format!("{}: {}", path.display(), err))?,
+        None => Config::default(),
+    };
+
+    if cli.multipass {
+        config.multipass = true;
+    }
+    if cli.pretty {
+        config.js2svg.pretty = true;
+    }
+    if let Some(indent) = cli.indent {
+        config.js2svg.indent = indent;
+    }
+    if let Some(eol) = cli.eol {
+        config.js2svg.eol = match eol {
+            EolArg::Lf => Eol::Lf,
+            EolArg::Crlf => Eol::Crlf,
+        };
+    }
+    if cli.final_newline {
+        config.js2svg.final_newline = true;
+    }
+    if let Some(precision) = cli.precision {
+        config.float_precision = Some(precision);
+    }
+    if let Some(datauri) = cli.datauri {
+        config.datauri = Some(match datauri {
+            DataUriArg::Base64 => DataUri::Base64,
+            DataUriArg::Enc => DataUri::Enc,
+            DataUriArg::Unenc => DataUri::Unenc,
+        });
+    }
+
+    Ok(config)
+}
+
+fn collect_svg_files(folder: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(folder)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                collect_svg_files(&path, recursive, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn process_file(cli: &Cli, config: &Config, input: &Path, output: Option<&Path>) -> CliResult<()> {
+    let data = if input == Path::new("-") {
+        let mut data = String::new();
+        io::stdin().read_to_string(&mut data).map_err(|err| format!("STDIN: {}", err))?;
+        data
+    } else {
+        fs::read_to_string(input).map_err(|err| format!("{}: {}", input.display(), err))?
+    };
+    process(cli, config, data, Some(input), Some(output.unwrap_or(input)))
+}
+
+fn process(cli: &Cli, config: &Config, data: String, input: Option<&Path>, output: Option<&Path>) -> CliResult<()> {
+    // the errors and the warnings are located in the input file
+    let mut config = config.clone();
+    config.path = input
+        .filter(|input| *input != Path::new("-"))
+        .map(|input| input.display().to_string());
+
+    let start = Instant::now();
+    let in_bytes = data.len();
+    let Output { data, warnings, .. } = optimize(data, &config).map_err(|err| err.to_string())?;
+    let time = start.elapsed();
+
+    if !cli.quiet {
+        for warning in warnings.iter() {
+            let name = warning.path.as_deref().unwrap_or("<input>");
+            let location = match (warning.line, warning.column) {
+                (Some(line), Some(column)) => format!("{}:{}:{}", name, line, column),
+                _ => name.to_string(),
+            };
+            match &warning.plugin {
+                Some(plugin) => eprintln!("warning: {}: {}: {}", location, plugin, warning.message),
+                None => eprintln!("warning: {}: {}", location, warning.message),
+            }
+        }
+    }
+
+    match output {
+        None => write_stdout(&data)?,
+        Some(output) if output == Path::new("-") => write_stdout(&data)?,
+        Some(output) => {
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent).map_err(|err| format!("{}: {}", parent.display(), err))?;
+            }
+            fs::write(output, &data).map_err(|err| format!("{}: {}", output.display(), err))?;
+
+            if !cli.quiet {
+                if let Some(input) = input {
+                    println!();
+                    println!("{}:", input.file_name().unwrap_or_default().to_string_lossy());
+                }
+                println!("Done in {} ms!", time.as_millis());
+                println!("{}", format_profit(in_bytes, data.len()));
+            }
+        },
+    }
+    Ok(())
+}
+
+fn write_stdout(data: &str) -> CliResult<()> {
+    io::stdout().write_all(data.as_bytes()).map_err(|err| format!("STDOUT: {}", err))
+}
+
+fn round(number: f64, precision: i32) -> f64 {
+    let scale = 10_f64.powi(precision);
+    (number * scale).round() / scale
+}
+
+/// Format like `1.2 KiB - 12.5% = 1.05 KiB`.
+fn format_profit(in_bytes: usize, out_bytes: usize) -> String {
+    let profit_percent = if in_bytes == 0 {
+        0.0
+    } else {
+        100.0 - out_bytes as f64 * 100.0 / in_bytes as f64
+    };
+    format!(
+        "{} KiB {} {}% = {} KiB",
+        round(in_bytes as f64 / 1024.0, 3),
+        if profit_percent < 0.0 { "+" } else { "-" },
+        round(profit_percent.abs(), 1),
+        round(out_bytes as f64 / 1024.0, 3),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profit() {
+        assert_eq!(format_profit(2048, 1536), "2 KiB - 25% = 1.5 KiB");
+        assert_eq!(format_profit(1000, 1100), "0.977 KiB + 10% = 1.074 KiB");
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+const SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>"#;
+const OPTIMIZED: &str = r#"<svg xmlns="http://www.w3.org/2000/svg"><rect/></svg>"#;
+
+fn svgo(dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_svgo-rs"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn write(path: &Path, data: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, data).unwrap();
+}
+
+#[test]
+fn output_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    write(&dir.path().join("a.svg"), SVG);
+    write(&dir.path().join("b.svg"), SVG);
+
+    let output = svgo(dir.path(), &["a.svg", "b.svg", "-o", "out"], "");
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(dir.path().join("out/a.svg")).unwrap(), OPTIMIZED);
+    assert_eq!(fs::read_to_string(dir.path().join("out/b.svg")).unwrap(), OPTIMIZED);
+    assert_eq!(fs::read_to_string(dir.path().join("a.svg")).unwrap(), SVG);
+}
+
+#[test]
+fn stdin_to_stdout() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = svgo(dir.path(), &["-"], SVG);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), OPTIMIZED);
+}
+
+#[test]
+fn input_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    write(&dir.path().join("in/a.svg"), SVG);
+    write(&dir.path().join("in/sub/b.svg"), SVG);
+    write(&dir.path().join("in/c.txt"), SVG);
+
+    let output = svgo(dir.path(), &["-f", "in", "-o", "out"], "");
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(dir.path().join("out/a.svg")).unwrap(), OPTIMIZED);
+    assert!(!dir.path().join("out/sub").exists());
+    assert!(!dir.path().join("out/c.txt").exists());
+
+    let output = svgo(dir.path(), &["-f", "in", "-r", "-q"], "");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(fs::read_to_string(dir.path().join("in/sub/b.svg")).unwrap(), OPTIMIZED);
+}
+
+#[test]
+fn parse_error() {
+    let dir = tempfile::tempdir().unwrap();
+    write(&dir.path().join("bad.svg"), "not svg");
+
+    let output = svgo(dir.path(), &["bad.svg"], "");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: bad.svg:1:1: "), "{}", stderr);
+    assert!(!stderr.contains("<input>"), "{}", stderr);
+}
@@ -0,0 +1,45 @@
+use std::io;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use svgo_rs::{optimize, parse_svg, stringify_svg, write_svg, Config, StringifyOptions};
+
+/// A map-like SVG with many paths and labels, a few megabytes for 20000 regions.
+fn map_svg(regions: usize) -> String {
+    let mut svg = String::from(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1000 1000">"#);
+    for i in 0..regions {
+        svg.push_str(&format!(
+            r##"<g id="region-{i}" class="region"><path fill="#ff0000" stroke="#000000" stroke-width="0.50" d="M{x} {y} L{x2} {y} L{x2} {y2} Z"/><text x="{x}" y="{y}">Region &amp; {i}</text></g>"##,
+            i = i,
+            x = i % 1000,
+            y = i / 20,
+            x2 = i % 1000 + 10,
+            y2 = i / 20 + 10,
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn bench_stringify(c: &mut Criterion) {
+    let doc = parse_svg(map_svg(20_000), None, &Default::default()).unwrap().doc;
+
+    c.bench_function("stringify_svg", |b| b.iter(|| stringify_svg(&doc, StringifyOptions::default())));
+    c.bench_function("stringify_svg pretty", |b| {
+        b.iter(|| stringify_svg(&doc, StringifyOptions {
+            pretty: true,
+            ..Default::default()
+        }))
+    });
+    c.bench_function("write_svg", |b| {
+        b.iter(|| write_svg(&doc, StringifyOptions::default(), io::sink()).unwrap())
+    });
+
+    // the output is stringified after every plugin to measure it
+    let input = map_svg(1_000);
+    c.bench_function("optimize preset-default", |b| {
+        b.iter(|| optimize(input.clone(), &Config::default()).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_stringify);
+criterion_main!(benches);
@@ -366,3 +366,31 @@ pub fn get_colors_props() -> Vec<&'static str> {
         "lighting-color",
     ]
 }
+
+// the namespaces of the data of editors, which does not change the rendering
+pub fn get_editor_namespaces() -> Vec<&'static str> {
+    vec![
+        "http://creativecommons.org/ns#",
+        "http://inkscape.sourceforge.net/DTD/sodipodi-0.dtd",
+        "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+        "http://www.inkscape.org/namespaces/inkscape",
+        "http://www.bohemiancoding.com/sketch/ns",
+        "http://ns.adobe.com/AdobeIllustrator/10.0/",
+        "http://ns.adobe.com/Graphs/1.0/",
+        "http://ns.adobe.com/AdobeSVGViewerExtensions/3.0/",
+        "http://ns.adobe.com/Variables/1.0/",
+        "http://ns.adobe.com/SaveForWeb/1.0/",
+        "http://ns.adobe.com/Extensibility/1.0/",
+        "http://ns.adobe.com/Flows/1.0/",
+        "http://ns.adobe.com/ImageReplacement/1.0/",
+        "http://ns.adobe.com/GenericCustomNamespace/1.0/",
+        "http://ns.adobe.com/XPath/1.0/",
+        "http://schemas.microsoft.com/visio/2003/SVGExtensions/",
+        "http://taptrix.com/vectorillustrator/svg_extensions",
+        "http://www.figma.com/figma/ns",
+        "http://purl.org/dc/elements/1.1/",
+        "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+        "http://www.serif.com/",
+        "http://www.vector.evaxdesign.sk",
+    ]
+}
@@ -0,0 +1,192 @@
+use std::{fs, path::Path};
+
+use serde::{de, Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::datauri::DataUri;
+use crate::error::Error;
+use crate::parser::ParseOptions;
+use crate::plugins::{self, Registry};
+use crate::stringifier::Js2SvgOptions;
+
+/// Options of a single `optimize` run.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// Path of the SVG file, used in error messages.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Plugins to run, in order.
+    #[serde(default = "default_plugins")]
+    pub plugins: Vec<PluginConfig>,
+    /// Rerun the plugins until the output stops shrinking.
+    #[serde(default)]
+    pub multipass: bool,
+    /// Measure the bytes saved by every plugin, stringifying the document after each of them.
+    #[serde(default)]
+    pub stats: bool,
+    /// Number of decimals kept by every plugin rounding numbers, unless its params set it.
+    #[serde(default)]
+    pub float_precision: Option<i32>,
+    /// Options of the parser.
+    #[serde(default)]
+    pub parser: ParseOptions,
+    /// Options of the output.
+    #[serde(default)]
+    pub js2svg: Js2SvgOptions,
+    /// Output a data URI instead of the SVG.
+    #[serde(default)]
+    pub datauri: Option<DataUri>,
+}
+
+fn default_plugins() -> Vec<PluginConfig> {
+    vec![PluginConfig::Name("preset-default".to_string())]
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            path: None,
+            plugins: default_plugins(),
+            multipass: false,
+            stats: false,
+            float_precision: None,
+            parser: Default::default(),
+            js2svg: Default::default(),
+            datauri: None,
+        }
+    }
+}
+
+impl Config {
+    /// Parse a `svgo.config.json`-style config, checking its plugins against the built-in ones.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let config: Config = serde_json::from_str(json).map_err(Error::Config)?;
+        config.validate(&Registry::new())?;
+        Ok(config)
+    }
+
+    /// Read and parse a `svgo.config.json`-style config file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let json = fs::read_to_string(path).map_err(Error::Io)?;
+        Self::from_json(&json)
+    }
+
+    /// Check that every plugin exists in the registry and that its params are valid.
+    pub fn validate(&self, registry: &Registry) -> Result<(), Error> {
+        plugins::prepare(registry, self).map(|_| ())
+    }
+}
+
+/// A plugin item of the config, either `"name"` or `{ "name": ..., "params": ... }`.
+#[derive(Debug, Clone)]
+pub enum PluginConfig {
+    Name(String),
+    WithParams {
+        name: String,
+        params: Option<Value>,
+    },
+}
+
+impl PluginConfig {
+    pub fn name(&self) -> &str {
+        match self {
+            PluginConfig::Name(name) => name,
+            PluginConfig::WithParams { name, .. } => name,
+        }
+    }
+
+    pub fn params(&self) -> Option<&Value> {
+        match self {
+            PluginConfig::Name(_) => None,
+            PluginConfig::WithParams { params, .. } => params.as_ref(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PluginConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(name) => Ok(PluginConfig::Name(name)),
+            Value::Object(mut plugin) => match plugin.remove("name") {
+                Some(Value::String(name)) => Ok(PluginConfig::WithParams {
+                    name,
+                    params: plugin.remove("params"),
+                }),
+                Some(_) => Err(de::Error::custom("plugin \"name\" must be a string")),
+                None => Err(de::Error::missing_field("name")),
+            },
+            _ => Err(de::Error::custom("expected a plugin name or an object with a \"name\"")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn svgo_config() {
+        let config = Config::from_json(r#"{
+            "multipass": true,
+            "js2svg": { "pretty": true, "indent": 2 },
+            "plugins": [
+                {
+                    "name": "preset-default",
+                    "params": {
+                        "overrides": {
+                            "cleanupIds": { "minify": false },
+                            "removeViewBox": false
+                        }
+                    }
+                },
+                "convertEllipseToCircle",
+                { "name": "convertColors", "params": { "currentColor": true } }
+            ]
+        }"#).unwrap();
+
+        assert!(config.multipass);
+        assert!(config.js2svg.pretty);
+        assert_eq!(config.js2svg.indent, 2);
+        let names: Vec<&str> = config.plugins.iter().map(|plugin| plugin.name()).collect();
+        assert_eq!(names, vec!["preset-default", "convertEllipseToCircle", "convertColors"]);
+    }
+
+    #[test]
+    fn unknown_plugin() {
+        let result = Config::from_json(r#"{ "plugins": ["cleanupIDs"] }"#);
+        assert_eq!(result.unwrap_err().to_string(), "Unknown plugin \"cleanupIDs\"");
+    }
+
+    #[test]
+    fn invalid_params() {
+        let result = Config::from_json(r#"{
+            "plugins": [{ "name": "convertColors", "params": { "shorthex": 1 } }]
+        }"#);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid params of plugin \"convertColors\": invalid type: integer `1`, expected a boolean",
+        );
+
+        let result = Config::from_json(r#"{
+            "plugins": [{ "name": "preset-default", "params": { "overrides": { "cleanupIds": { "remove": "no" } } } }]
+        }"#);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid params of plugin \"cleanupIds\": invalid type: string \"no\", expected a boolean",
+        );
+    }
+
+    #[test]
+    fn invalid_plugin_item() {
+        let result = Config::from_json(r#"{
+            "plugins": [
+                { "params": {} }
+            ]
+        }"#);
+        assert_eq!(result.unwrap_err().to_string(), "Invalid config: missing field `name` at line 4 column 13");
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+
+const PREFIX: &str = "data:image/svg+xml";
+
+/// How to encode the output as a data URI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataUri {
+    /// `data:image/svg+xml;base64,...`
+    Base64,
+    /// `data:image/svg+xml,...` with only the characters which are not allowed in a URL escaped.
+    Enc,
+    /// `data:image/svg+xml,...` without any escaping.
+    Unenc,
+}
+
+/// Whether the byte must be percent-encoded in a data URI used as CSS `url("...")`.
+///
+/// Unlike `encodeURIComponent`, spaces, quotes other than `"` and most punctuation are kept.
+fn must_escape(byte: u8) -> bool {
+    matches!(
+        byte,
+        0..=0x1f | 0x7f.. | b'"' | b'#' | b'%' | b'<' | b'>' | b'[' | b'\\' | b']' | b'^' | b'`' | b'{' | b'|' | b'}'
+    )
+}
+
+pub fn encode_svg_datauri(svg: &str, kind: DataUri) -> String {
+    match kind {
+        DataUri::Base64 => format!("{};base64,{}", PREFIX, STANDARD.encode(svg)),
+        DataUri::Enc => {
+            let mut uri = String::with_capacity(PREFIX.len() + 1 + svg.len());
+            uri.push_str(PREFIX);
+            uri.push(',');
+            for byte in svg.bytes() {
+                // the non-ASCII bytes are all escaped, so the kept ones are ASCII characters
+                if must_escape(byte) {
+                    write!(uri, "%{:02X}", byte).unwrap();
+                } else {
+                    uri.push(byte as char);
+                }
+            }
+            uri
+        },
+        DataUri::Unenc => format!("{},{}", PREFIX, svg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg"><path fill="#f00" d="M0 0h1"/><text>é</text></svg>"##;
+
+    #[test]
+    fn base64() {
+        assert_eq!(
+            encode_svg_datauri("<svg/>", DataUri::Base64),
+            "data:image/svg+xml;base64,PHN2Zy8+",
+        );
+    }
+
+    #[test]
+    fn enc() {
+        let uri = encode_svg_datauri(SVG, DataUri::Enc);
+        assert_eq!(
+            uri,
+            "data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22%3E%3Cpath fill=%22%23f00%22 d=%22M0 0h1%22/%3E%3Ctext%3E%C3%A9%3C/text%3E%3C/svg%3E",
+        );
+        assert!(uri.len() < encode_svg_datauri(SVG, DataUri::Base64).len());
+    }
+
+    #[test]
+    fn unenc() {
+        assert_eq!(encode_svg_datauri("<svg/>", DataUri::Unenc), "data:image/svg+xml,<svg/>");
+    }
+}
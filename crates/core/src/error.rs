@@ -0,0 +1,123 @@
+use std::{fmt, io, path::PathBuf};
+
+use serde::Serialize;
+
+/// An error found in the input SVG.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub message: String,
+    /// Path of the SVG file, from the `path` of the config.
+    pub path: Option<String>,
+    /// 1-based line of the error.
+    pub line: u32,
+    /// 1-based column of the error.
+    pub column: u32,
+    /// The source line of the error with a caret under the column.
+    pub snippet: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.as_deref().unwrap_or("<input>");
+        write!(f, "{}:{}:{}: {}\n\n{}", path, self.line, self.column, self.message, self.snippet)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A problem which did not stop the optimization.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Warning {
+    pub message: String,
+    /// The plugin reporting the problem, none for the parser.
+    pub plugin: Option<String>,
+    /// Path of the SVG file, from the `path` of the config.
+    pub path: Option<String>,
+    /// 1-based line of the problem, if known.
+    pub line: Option<u32>,
+    /// 1-based column of the problem, if known.
+    pub column: Option<u32>,
+    /// The source line of the problem with a caret under the column, if known.
+    pub snippet: Option<String>,
+}
+
+impl From<ParseError> for Warning {
+    fn from(error: ParseError) -> Self {
+        Warning {
+            message: error.message,
+            plugin: None,
+            path: error.path,
+            line: Some(error.line),
+            column: Some(error.column),
+            snippet: Some(error.snippet),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input is not a well-formed SVG.
+    Parse(ParseError),
+    /// The config file could not be read.
+    Io(io::Error),
+    /// An SVG file given to `optimize_many` could not be read.
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The config does not have the expected shape.
+    Config(serde_json::Error),
+    /// The config names a plugin which does not exist.
+    UnknownPlugin(String),
+    /// A plugin is registered with the name of another plugin.
+    DuplicatePlugin(String),
+    /// The params of a plugin could not be deserialized.
+    InvalidParams {
+        plugin: String,
+        source: serde_json::Error,
+    },
+    /// The overrides of a preset name a plugin which is not part of it.
+    UnknownOverride {
+        preset: String,
+        plugin: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(error) => error.fmt(f),
+            Error::Io(error) => error.fmt(f),
+            Error::Read { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Config(error) => write!(f, "Invalid config: {}", error),
+            Error::UnknownPlugin(name) => write!(f, "Unknown plugin \"{}\"", name),
+            Error::DuplicatePlugin(name) => write!(f, "A plugin named \"{}\" is already registered", name),
+            Error::InvalidParams { plugin, source } => {
+                write!(f, "Invalid params of plugin \"{}\": {}", plugin, source)
+            }
+            Error::UnknownOverride { preset, plugin } => {
+                write!(f, "You are trying to configure \"{}\" which is not part of {}", plugin, preset)
+            }
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::Read { source, .. } => Some(source),
+            Error::Config(error) => Some(error),
+            Error::InvalidParams { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
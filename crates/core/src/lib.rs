@@ -1,44 +1,532 @@
 #![deny(clippy::all)]
 
-#[cfg(feature = "node")]
-#[macro_use]
-extern crate napi_derive;
-
 mod collections;
+mod config;
+mod datauri;
+mod error;
+pub mod namespaces;
 mod parser;
-mod plugins;
+pub mod plugins;
 mod stringifier;
 
 #[cfg(test)]
 mod testing;
 
-use stringifier::{stringify_svg, StringifyOptions};
+use std::{fs, path::PathBuf};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::Serialize;
+
+use plugins::PreparedPlugin;
+use stringifier::Stringifier;
+
+pub use config::{Config, PluginConfig};
+pub use datauri::DataUri;
+pub use error::{Error, ParseError, Warning};
+pub use parser::{parse_doctype, parse_svg, Location, ParseOptions, ParsedSvg, Source};
+pub use plugins::{Context, DynPlugin, LocalRegistry, Plugin, Registry};
+pub use stringifier::{doctype_declaration, stringify_svg, write_svg, Eol, Js2SvgOptions, StringifyOptions};
 
-#[cfg(feature = "node")]
-#[napi(object)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Output {
     pub data: String,
+    /// Errors the parser recovered from and problems reported by the plugins.
+    pub warnings: Vec<Warning>,
+    /// How many times the plugins were run.
+    pub passes: u32,
+    /// Size of the input in bytes.
+    pub original_size: u32,
+    /// Size of `data` in bytes.
+    pub optimized_size: u32,
+    /// Stats of every plugin, in the order they ran.
+    pub plugins: Vec<PluginStats>,
 }
 
-#[cfg(not(feature = "node"))]
-pub struct Output {
-    pub data: String,
+/// What a plugin did, summed over all the passes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginStats {
+    pub name: String,
+    /// Time spent in the plugin, in milliseconds.
+    pub time: f64,
+    /// How many bytes the plugin removed from the output, negative when it added some.
+    ///
+    /// Only measured with the `stats` option, as it stringifies the document after every plugin.
+    pub saved_bytes: Option<i64>,
 }
 
+const MAX_MULTIPASS_COUNT: u32 = 10;
+
 /// The core of SVGO
-pub fn optimize(input: String) -> Output {
-    let mut doc = parser::parse_svg(input).unwrap();
-
-    plugins::cleanup_attrs::apply(&mut doc);
-    plugins::cleanup_enable_background::apply(&mut doc);
-    plugins::cleanup_ids::apply(&mut doc, &Default::default());
-    plugins::cleanup_numeric_values::apply(&mut doc, &Default::default());
-
-    let data = stringify_svg(&doc, StringifyOptions {
-        pretty: true,
-        ..Default::default()
-    });
-    Output {
+pub fn optimize(input: String, config: &Config) -> Result<Output, Error> {
+    optimize_with_registry(input, config, &Registry::new())
+}
+
+/// Like [`optimize`], looking up the configured plugins in the given registry.
+pub fn optimize_with_registry(input: String, config: &Config, registry: &Registry) -> Result<Output, Error> {
+    let plugins = plugins::prepare(registry, config)?;
+    run(input, config.path.as_deref(), config, &plugins)
+}
+
+/// Like [`optimize_with_registry`], with plugins which can't leave the current thread.
+pub fn optimize_with_local_registry(input: String, config: &Config, registry: &LocalRegistry) -> Result<Output, Error> {
+    let plugins = plugins::prepare(registry, config)?;
+    run(input, config.path.as_deref(), config, &plugins)
+}
+
+/// An SVG to optimize with [`optimize_many`].
+#[derive(Debug, Clone)]
+pub enum Input {
+    Data(String),
+    Path(PathBuf),
+}
+
+impl From<String> for Input {
+    fn from(data: String) -> Self {
+        Input::Data(data)
+    }
+}
+
+impl From<PathBuf> for Input {
+    fn from(path: PathBuf) -> Self {
+        Input::Path(path)
+    }
+}
+
+/// Optimize many SVGs with the same config, in parallel when the `parallel` feature is enabled.
+///
+/// The results are in the order of the inputs. An invalid config fails the whole batch.
+pub fn optimize_many(inputs: Vec<Input>, config: &Config) -> Result<Vec<Result<Output, Error>>, Error> {
+    optimize_many_with_registry(inputs, config, &Registry::new())
+}
+
+/// Like [`optimize_many`], looking up the configured plugins in the given registry.
+pub fn optimize_many_with_registry(
+    inputs: Vec<Input>,
+    config: &Config,
+    registry: &Registry,
+) -> Result<Vec<Result<Output, Error>>, Error> {
+    let plugins = plugins::prepare(registry, config)?;
+    let optimize_input = |input: Input| {
+        match input {
+            Input::Data(data) => run(data, config.path.as_deref(), config, &plugins),
+            Input::Path(path) => {
+                let data = fs::read_to_string(&path).map_err(|source| Error::Read {
+                    path: path.clone(),
+                    source,
+                })?;
+                run(data, Some(&path.to_string_lossy()), config, &plugins)
+            },
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    let outputs = inputs.into_par_iter().map(optimize_input).collect();
+    #[cfg(not(feature = "parallel"))]
+    let outputs = inputs.into_iter().map(optimize_input).collect();
+    Ok(outputs)
+}
+
+/// Run `f`, returning how long it took in milliseconds.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn timed(f: impl FnOnce()) -> f64 {
+    let start = Instant::now();
+    f();
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// `Instant` panics on `wasm32-unknown-unknown`, plugins are not timed there.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn timed(f: impl FnOnce()) -> f64 {
+    f();
+    0.0
+}
+
+fn run<P: ?Sized + DynPlugin>(
+    input: String,
+    path: Option<&str>,
+    config: &Config,
+    plugins: &[PreparedPlugin<P>],
+) -> Result<Output, Error> {
+    let original_size = input.len() as u32;
+    let parser::ParsedSvg { mut doc, source, warnings } = parser::parse_svg(input, path, &config.parser)?;
+    let mut warnings: Vec<Warning> = warnings.into_iter().map(Warning::from).collect();
+
+    let mut stringifier = Stringifier::new((&config.js2svg).into());
+    let mut stats: Vec<PluginStats> = plugins
+        .iter()
+        .map(|plugin| PluginStats {
+            name: plugin.name().to_string(),
+            time: 0.0,
+            saved_bytes: config.stats.then_some(0),
+        })
+        .collect();
+    let mut context = Context::new(path.map(str::to_string));
+
+    let max_passes = if config.multipass { MAX_MULTIPASS_COUNT } else { 1 };
+    let mut size = if config.stats { stringifier.size(&doc) } else { 0 };
+    let mut prev_result_size = usize::MAX;
+    let mut passes = 0;
+
+    while passes < max_passes {
+        context.multipass_count = passes;
+        passes += 1;
+
+        for (plugin, stats) in plugins.iter().zip(stats.iter_mut()) {
+            stats.time += timed(|| plugin.apply(&mut doc, &mut context));
+
+            // only the size is needed between the plugins
+            if let Some(saved_bytes) = &mut stats.saved_bytes {
+                let prev_size = size;
+                size = stringifier.size(&doc);
+                *saved_bytes += prev_size as i64 - size as i64;
+            }
+
+            for diagnostic in context.take_diagnostics() {
+                let location = source.locate(diagnostic.span);
+                let warning = Warning {
+                    message: diagnostic.message,
+                    plugin: Some(plugin.name().to_string()),
+                    path: path.map(str::to_string),
+                    line: location.as_ref().map(|location| location.line),
+                    column: location.as_ref().map(|location| location.column),
+                    snippet: location.map(|location| location.snippet),
+                };
+                // the same problem is usually found again by the next passes
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+
+        if passes == max_passes {
+            break;
+        }
+        // with the stats, the size after the last plugin is already known
+        if !config.stats {
+            size = stringifier.size(&doc);
+        }
+        if size < prev_result_size {
+            prev_result_size = size;
+        } else {
+            break;
+        }
+    }
+
+    let mut data = stringifier.stringify(&doc);
+    if let Some(kind) = config.datauri {
+        data = datauri::encode_svg_datauri(&data, kind);
+    }
+
+    Ok(Output {
+        optimized_size: data.len() as u32,
         data,
+        warnings,
+        passes,
+        original_size,
+        plugins: stats,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn config(json: &str) -> Config {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn runs_plugins_in_order() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <ellipse rx="5" ry="5.0001"/>
+</svg>"#;
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "plugins": [
+                { "name": "cleanupNumericValues", "params": { "floatPrecision": 2 } },
+                "convertEllipseToCircle"
+            ]
+        }"#)).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg"><circle r="5"/></svg>"#);
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "plugins": [
+                "convertEllipseToCircle",
+                { "name": "cleanupNumericValues", "params": { "floatPrecision": 2 } }
+            ]
+        }"#)).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg"><ellipse rx="5" ry="5"/></svg>"#);
+    }
+
+    #[test]
+    fn js2svg() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>"#;
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "plugins": [],
+            "js2svg": { "pretty": true, "indent": 2, "eol": "crlf", "finalNewline": true }
+        }"#)).unwrap();
+        assert_eq!(output.data, "<svg xmlns=\"http://www.w3.org/2000/svg\">\r\n  <g>\r\n    <rect/>\r\n  </g>\r\n</svg>\r\n");
+    }
+
+    #[test]
+    fn multipass() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <ellipse rx="5" ry="5.0001"/>
+</svg>"#;
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "plugins": ["convertEllipseToCircle", "cleanupNumericValues"]
+        }"#)).unwrap();
+        assert_eq!(output.passes, 1);
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg"><ellipse rx="5" ry="5"/></svg>"#);
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "multipass": true,
+            "plugins": ["convertEllipseToCircle", "cleanupNumericValues"]
+        }"#)).unwrap();
+        assert_eq!(output.passes, 3);
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg"><circle r="5"/></svg>"#);
+    }
+
+    #[test]
+    fn datauri() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>"#;
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "plugins": ["collapseGroups"],
+            "datauri": "enc"
+        }"#)).unwrap();
+        assert_eq!(output.data, "data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22%3E%3Crect/%3E%3C/svg%3E");
+        assert_eq!(output.optimized_size as usize, output.data.len());
+    }
+
+    #[test]
+    fn float_precision() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg" width="1.23456"/>"#;
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "floatPrecision": 1,
+            "plugins": ["cleanupNumericValues"]
+        }"#)).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg" width="1.2"/>"#);
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "floatPrecision": 1,
+            "plugins": [{ "name": "cleanupNumericValues", "params": { "floatPrecision": 2 } }]
+        }"#)).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg" width="1.23"/>"#);
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "floatPrecision": 1,
+            "plugins": [{ "name": "preset-default", "params": { "overrides": { "cleanupNumericValues": { "defaultPx": false } } } }]
+        }"#)).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg" width="1.2"/>"#);
+    }
+
+    #[test]
+    fn stats() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <style>.a { fill: red }</style>
+    <g><rect id="unused" class="a"/></g>
+</svg>"#;
+
+        let output = optimize(input.to_string(), &config(r#"{
+            "multipass": true,
+            "stats": true,
+            "plugins": ["cleanupIds", "collapseGroups"]
+        }"#)).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg"><style>.a { fill: red }</style><rect id="unused" class="a"/></svg>"#);
+        assert_eq!(output.original_size as usize, input.len());
+        assert_eq!(output.optimized_size as usize, output.data.len());
+
+        let names: Vec<&str> = output.plugins.iter().map(|stats| stats.name.as_str()).collect();
+        assert_eq!(names, vec!["cleanupIds", "collapseGroups"]);
+        assert_eq!(output.plugins[0].saved_bytes, Some(0));
+        assert_eq!(output.plugins[1].saved_bytes, Some(7));
+
+        assert_eq!(output.warnings.len(), 1);
+        assert_eq!(output.warnings[0].plugin.as_deref(), Some("cleanupIds"));
+        assert_eq!(output.warnings[0].line, None);
+
+        let output = optimize(input.to_string(), &config(r#"{ "plugins": ["collapseGroups"] }"#)).unwrap();
+        assert_eq!(output.plugins[0].saved_bytes, None);
+    }
+
+    #[test]
+    fn many() {
+        let inputs = vec![
+            Input::Data(r#"<svg xmlns="http://www.w3.org/2000/svg"><g><rect/></g></svg>"#.to_string()),
+            Input::Data("<svg><rect></svg>".to_string()),
+            Input::Path(PathBuf::from("tests/not-found.svg")),
+            Input::Data(r#"<svg xmlns="http://www.w3.org/2000/svg"><g><circle/></g></svg>"#.to_string()),
+        ];
+        let outputs = optimize_many(inputs, &config(r#"{ "plugins": ["collapseGroups"] }"#)).unwrap();
+
+        assert_eq!(outputs.len(), 4);
+        assert_eq!(outputs[0].as_ref().unwrap().data, r#"<svg xmlns="http://www.w3.org/2000/svg"><rect/></svg>"#);
+        assert!(matches!(outputs[1], Ok(ref output) if !output.warnings.is_empty()));
+        assert!(matches!(outputs[2], Err(Error::Read { ref path, .. }) if path.ends_with("not-found.svg")));
+        assert!(outputs[2].as_ref().unwrap_err().to_string().starts_with("tests/not-found.svg: "));
+        assert_eq!(outputs[3].as_ref().unwrap().data, r#"<svg xmlns="http://www.w3.org/2000/svg"><circle/></svg>"#);
+
+        let result = optimize_many(vec![], &config(r#"{ "plugins": ["cleanupIDs"] }"#));
+        assert!(matches!(result, Err(Error::UnknownPlugin(_))));
+    }
+
+    #[test]
+    fn custom_plugin() {
+        struct RemoveTitle;
+
+        impl Plugin for RemoveTitle {
+            type Params = ();
+
+            fn name(&self) -> &str {
+                "removeTitle"
+            }
+
+            fn description(&self) -> &str {
+                "removes <title>"
+            }
+
+            fn apply(&self, doc: &mut swc_xml_ast::Document, _: &(), _: &mut Context) {
+                for child in doc.children.iter_mut() {
+                    if let swc_xml_ast::Child::Element(svg) = child {
+                        svg.children.retain(|child| !matches!(child, swc_xml_ast::Child::Element(n) if &*n.tag_name == "title"));
+                    }
+                }
+            }
+        }
+
+        let mut registry = Registry::new();
+        registry.register(RemoveTitle).unwrap();
+        assert!(matches!(registry.register(RemoveTitle), Err(Error::DuplicatePlugin(name)) if name == "removeTitle"));
+
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+    <title>Icon</title>
+    <ellipse rx="5" ry="5"/>
+</svg>"#;
+        let output = optimize_with_registry(input.to_string(), &config(r#"{
+            "plugins": ["removeTitle", "convertEllipseToCircle"]
+        }"#), &registry).unwrap();
+        assert_eq!(output.data, r#"<svg xmlns="http://www.w3.org/2000/svg"><circle r="5"/></svg>"#);
+    }
+
+    #[test]
+    fn local_plugin() {
+        use std::{cell::Cell, rc::Rc};
+
+        // not `Send`, like the plugins calling JavaScript
+        struct Count(Rc<Cell<u32>>);
+
+        impl Plugin for Count {
+            type Params = ();
+
+            fn name(&self) -> &str {
+                "count"
+            }
+
+            fn description(&self) -> &str {
+                "counts the runs"
+            }
+
+            fn apply(&self, _: &mut swc_xml_ast::Document, _: &(), _: &mut Context) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let count = Rc::new(Cell::new(0));
+        let registry = Registry::new();
+        let mut local_registry = LocalRegistry::new(&registry);
+        local_registry.register(Count(count.clone())).unwrap();
+        assert!(matches!(local_registry.register(Count(count.clone())), Err(Error::DuplicatePlugin(_))));
+
+        let output = optimize_with_local_registry("<svg><g><rect/></g></svg>".to_string(), &config(r#"{
+            "plugins": ["count", "collapseGroups"]
+        }"#), &local_registry).unwrap();
+        assert_eq!(output.data, "<svg><rect/></svg>");
+        assert_eq!(count.get(), 1);
+
+        let mut local_registry = LocalRegistry::new(&registry);
+        local_registry.register_as("count#0".to_string(), Count(count.clone())).unwrap();
+        local_registry.register_as("count#1".to_string(), Count(count.clone())).unwrap();
+        assert!(matches!(
+            local_registry.register_as("count#1".to_string(), Count(count.clone())),
+            Err(Error::DuplicatePlugin(key)) if key == "count#1"
+        ));
+        let output = optimize_with_local_registry("<svg/>".to_string(), &config(r#"{
+            "stats": true,
+            "plugins": ["count#0", "count#1"]
+        }"#), &local_registry).unwrap();
+        assert_eq!(count.get(), 3);
+        let names: Vec<_> = output.plugins.iter().map(|plugin| plugin.name.as_str()).collect();
+        assert_eq!(names, ["count", "count"]);
+    }
+
+    #[test]
+    fn parse_error() {
+        let result = optimize("<svg>\n  <rect/>\n</svg>\nnot svg".to_string(), &Default::default());
+        assert!(result.is_ok());
+
+        let result = optimize("not svg".to_string(), &Default::default());
+        match result {
+            Err(Error::Parse(error)) => {
+                assert_eq!(error.line, 1);
+                assert_eq!(error.column, 1);
+                assert_eq!(error.snippet, "not svg\n^");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let result = optimize("not svg".to_string(), &config(r#"{ "path": "icons/logo.svg" }"#));
+        assert!(result.unwrap_err().to_string().starts_with("icons/logo.svg:1:1: "));
+    }
+
+    #[test]
+    fn parse_warnings() {
+        let output = optimize("<svg>\n    <g>\n</svg>".to_string(), &Default::default()).unwrap();
+        assert_eq!(output.warnings.len(), 1);
+        assert_eq!(output.warnings[0].message, "Opening and ending tag mismatch");
+        assert_eq!(output.warnings[0].line, Some(3));
+        assert_eq!(output.warnings[0].column, Some(1));
+    }
+
+    #[test]
+    fn plugin_warnings() {
+        let input = "<svg xmlns=\"http://www.w3.org/2000/svg\">\n  <rect id=\"a\"/>\n  <circle id=\"a\"/>\n</svg>";
+        let output = optimize(input.to_string(), &config(r#"{
+            "path": "icon.svg",
+            "plugins": ["cleanupIds"]
+        }"#)).unwrap();
+        assert_eq!(output.warnings, vec![Warning {
+            message: "Removed duplicate ID \"a\"".to_string(),
+            plugin: Some("cleanupIds".to_string()),
+            path: Some("icon.svg".to_string()),
+            line: Some(3),
+            column: Some(3),
+            snippet: Some("  <circle id=\"a\"/>\n  ^".to_string()),
+        }]);
+    }
+
+    #[test]
+    fn unknown_plugin() {
+        let result = optimize("<svg/>".to_string(), &config(r#"{ "plugins": ["removeFoo"] }"#));
+        assert!(matches!(result, Err(Error::UnknownPlugin(name)) if name == "removeFoo"));
+    }
+
+    #[test]
+    fn invalid_params() {
+        let result = optimize("<svg/>".to_string(), &config(r#"{
+            "plugins": [{ "name": "cleanupIds", "params": { "minify": "yes" } }]
+        }"#));
+        assert!(matches!(result, Err(Error::InvalidParams { plugin, .. }) if plugin == "cleanupIds"));
     }
 }
@@ -0,0 +1,200 @@
+//! Namespaces of the elements and the attributes, resolving their prefixes against the `xmlns` declarations
+//! in scope, so that `<svg:g>` is a group and `xl:href` a link when the prefixes are declared so.
+//!
+//! @example
+//! fn visit_mut_element(&mut self, n: &mut Element) {
+//!     self.scope.enter(n);
+//!     if self.scope.is_svg_element(n, "use") {
+//!         let href = n.attributes.iter().find(|attr| self.scope.is_href(attr));
+//!     }
+//!     n.visit_mut_children_with(self);
+//!     self.scope.leave();
+//! }
+
+use swc_xml_ast::{Attribute, Element};
+
+use crate::collections::get_editor_namespaces;
+
+pub const SVG: &str = "http://www.w3.org/2000/svg";
+pub const XLINK: &str = "http://www.w3.org/1999/xlink";
+pub const XML: &str = "http://www.w3.org/XML/1998/namespace";
+pub const XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+
+/// Whether the namespace is the one of an editor like Inkscape, Illustrator or Sketch.
+pub fn is_editor_namespace(uri: &str) -> bool {
+    get_editor_namespaces().contains(&uri)
+}
+
+/// The prefix and the local part of a qualified name, e.g. `xlink` and `href` of `xlink:href`.
+pub fn split_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (Some(prefix), local),
+        None => (None, name),
+    }
+}
+
+// the prefix declared by an attribute, `""` for the default namespace
+fn declared_prefix(attr: &Attribute) -> Option<&str> {
+    match &*attr.name {
+        "xmlns" => Some(""),
+        name => name.strip_prefix("xmlns:"),
+    }
+}
+
+/// The `xmlns` declarations in scope, entered and left along with the elements.
+#[derive(Debug, Default, Clone)]
+pub struct Scope {
+    // the prefixes and namespaces declared by the entered elements, innermost last
+    declarations: Vec<(String, String)>,
+    // how many declarations there were before entering each element
+    marks: Vec<usize>,
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Bring the declarations of the element in scope, until it is left.
+    pub fn enter(&mut self, n: &Element) {
+        self.marks.push(self.declarations.len());
+        for attr in n.attributes.iter() {
+            if let Some(prefix) = declared_prefix(attr) {
+                let uri = attr.value.as_deref().unwrap_or_default();
+                self.declarations.push((prefix.to_string(), uri.to_string()));
+            }
+        }
+    }
+
+    /// Leave the element entered last.
+    pub fn leave(&mut self) {
+        if let Some(mark) = self.marks.pop() {
+            self.declarations.truncate(mark);
+        }
+    }
+
+    // `Some("")` when the prefix is undeclared with `xmlns=""`
+    fn lookup(&self, prefix: &str) -> Option<&str> {
+        match prefix {
+            "xml" => Some(XML),
+            "xmlns" => Some(XMLNS),
+            _ => self
+                .declarations
+                .iter()
+                .rev()
+                .find(|(declared, _)| declared == prefix)
+                .map(|(_, uri)| uri.as_str()),
+        }
+    }
+
+    /// The namespace bound to the prefix, `""` for the default namespace.
+    pub fn resolve(&self, prefix: &str) -> Option<&str> {
+        self.lookup(prefix).filter(|uri| !uri.is_empty())
+    }
+
+    /// The namespace of the element, SVG for unprefixed names without a default namespace.
+    ///
+    /// The declarations of the element itself are taken into account even when it was not entered,
+    /// so that the children can be checked from their parent.
+    pub fn element_namespace<'a>(&'a self, n: &'a Element) -> Option<&'a str> {
+        let prefix = split_name(&n.tag_name).0.unwrap_or_default();
+        let own = n.attributes.iter().rev().find(|attr| declared_prefix(attr) == Some(prefix));
+        let uri = match own {
+            Some(attr) => attr.value.as_deref(),
+            None => self.lookup(prefix),
+        };
+        match uri {
+            Some("") => None,
+            None if prefix.is_empty() => Some(SVG),
+            uri => uri,
+        }
+    }
+
+    /// The local name of an SVG element, e.g. `g` of `<g>` and `<svg:g>`, `None` for other namespaces.
+    pub fn svg_name<'a>(&self, n: &'a Element) -> Option<&'a str> {
+        (self.element_namespace(n) == Some(SVG)).then(|| split_name(&n.tag_name).1)
+    }
+
+    /// Whether the element is the SVG element `local`.
+    pub fn is_svg_element(&self, n: &Element, local: &str) -> bool {
+        self.svg_name(n) == Some(local)
+    }
+
+    /// The namespace of an attribute of an entered element, `None` when it has no prefix.
+    pub fn attr_namespace(&self, attr: &Attribute) -> Option<&str> {
+        split_name(&attr.name).0.and_then(|prefix| self.resolve(prefix))
+    }
+
+    /// Whether the attribute of an entered element is `local` in the namespace, `None` meaning no prefix.
+    pub fn is_attr(&self, attr: &Attribute, namespace: Option<&str>, local: &str) -> bool {
+        match split_name(&attr.name) {
+            (Some(prefix), name) => name == local && namespace.is_some() && self.resolve(prefix) == namespace,
+            (None, name) => name == local && namespace.is_none(),
+        }
+    }
+
+    /// Whether the attribute of an entered element is a link, `href` of SVG 2 or `xlink:href` by any prefix.
+    pub fn is_href(&self, attr: &Attribute) -> bool {
+        self.is_attr(attr, None, "href") || self.is_attr(attr, Some(XLINK), "href")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+
+    fn element(tag_name: &str, attributes: &[(&str, &str)]) -> Element {
+        Element {
+            span: DUMMY_SP,
+            tag_name: tag_name.into(),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| Attribute {
+                    span: DUMMY_SP,
+                    namespace: None,
+                    prefix: None,
+                    name: (*name).into(),
+                    raw_name: None,
+                    value: Some((*value).into()),
+                    raw_value: None,
+                })
+                .collect(),
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn scope() {
+        let root = element("svg", &[("xmlns", SVG), ("xmlns:xl", XLINK), ("xmlns:s", SVG)]);
+        let mut scope = Scope::new();
+        assert!(scope.is_svg_element(&root, "svg"));
+        scope.enter(&root);
+
+        let link = element("s:use", &[("xl:href", "#a"), ("xlink:href", "#b"), ("href", "#c")]);
+        assert_eq!(scope.svg_name(&link), Some("use"));
+        scope.enter(&link);
+        let hrefs: Vec<_> = link.attributes.iter().filter(|attr| scope.is_href(attr)).collect();
+        assert_eq!(hrefs.len(), 2);
+        assert_eq!(scope.attr_namespace(&link.attributes[1]), None);
+        scope.leave();
+
+        let foreign = element("g", &[("xmlns", "urn:a"), ("xmlns:xl", "urn:b")]);
+        assert_eq!(scope.svg_name(&foreign), None);
+        scope.enter(&foreign);
+        assert_eq!(scope.resolve("xl"), Some("urn:b"));
+        assert_eq!(scope.resolve("xml"), Some(XML));
+        assert!(!scope.is_svg_element(&element("g", &[]), "g"));
+        assert!(scope.is_svg_element(&element("g", &[("xmlns", SVG)]), "g"));
+        assert_eq!(scope.element_namespace(&element("g", &[("xmlns", "")])), None);
+        scope.leave();
+
+        assert_eq!(scope.resolve("xl"), Some(XLINK));
+        scope.leave();
+        assert_eq!(scope.resolve("xl"), None);
+        assert!(scope.is_svg_element(&element("g", &[]), "g"));
+        assert_eq!(scope.element_namespace(&element("a:g", &[])), None);
+        assert!(is_editor_namespace("http://www.inkscape.org/namespaces/inkscape"));
+    }
+}
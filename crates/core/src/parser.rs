@@ -1,70 +1,720 @@
-use std::sync::Arc;
+use std::{collections::HashMap, ops::Range};
 
 use regex::Regex;
+use serde::Deserialize;
 use swc_xml_ast::*;
 use swc_xml_visit::{VisitMut, VisitMutWith};
 use swc_xml_parser::{parse_file_as_document, parser, error::Error};
-use swc_core::common::{SourceMap, FileName};
+use swc_core::common::{sync::Lrc, BytePos, SourceMap, FileName, SourceFile, Span, DUMMY_SP};
 
 use crate::collections::get_text_elems;
+use crate::error::ParseError;
 
-struct Visitor {
+/// How the parser treats whitespace, by default like SVGO.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseOptions {
+    /// Keep the text of the elements with `xml:space="preserve"` on themselves or an ancestor,
+    /// unless a closer `xml:space="default"` overrides it.
+    #[serde(default = "default_true")]
+    pub preserve_xml_space: bool,
+    /// Keep the whitespace of `<style>` and `<script>`, and of the whole `<foreignObject>` subtrees.
+    #[serde(default)]
+    pub preserve_raw_text: bool,
+    /// Keep the comments as is instead of trimming them.
+    #[serde(default)]
+    pub verbatim_comments: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            preserve_xml_space: true,
+            preserve_raw_text: false,
+            verbatim_comments: false,
+        }
+    }
+}
+
+/// Whether the element sets `xml:space`, `Some(true)` for `preserve`.
+pub(crate) fn xml_space(n: &Element) -> Option<bool> {
+    n.attributes
+        .iter()
+        .find(|attr| &*attr.name == "xml:space")
+        .and_then(|attr| match attr.value.as_deref() {
+            Some("preserve") => Some(true),
+            Some("default") => Some(false),
+            _ => None,
+        })
+}
+
+/// The internal subset of a doctype, the declarations between `[` and `]`.
+pub(crate) fn internal_subset(n: &DocumentType) -> Option<&str> {
+    let raw = n.raw.as_deref()?;
+    subset_range(raw).map(|range| &raw[range])
+}
+
+/// Where the internal subset is in the raw doctype.
+fn subset_range(raw: &str) -> Option<Range<usize>> {
+    let mut quote = None;
+    for (i, c) in raw.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => {
+                let end = raw.rfind(']')?;
+                return (end > i).then(|| i + 1..end);
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+// the longest replacement text of an entity, against exponential expansions like billion laughs
+const MAX_ENTITY_LEN: usize = 1 << 16;
+// the most bytes all the references of a document may expand to, against the many references to long entities
+const MAX_EXPANSION_LEN: usize = 1 << 24;
+
+/// The general entities declared in the internal subset, with their replacement text.
+///
+/// The offsets in the subset of the references which would make a replacement text longer than `MAX_ENTITY_LEN`
+/// are pushed to `truncated`.
+fn declared_entities(subset: &str, truncated: &mut Vec<usize>) -> HashMap<String, String> {
+    let re = Regex::new(r#"(?s)<!--.*?-->|<!ENTITY\s+([^\s%]\S*)\s+(?:"([^"]*)"|'([^']*)')\s*>"#).unwrap();
+    let mut entities = HashMap::new();
+    for caps in re.captures_iter(subset) {
+        let Some(name) = caps.get(1).map(|name| name.as_str()) else {
+            continue;
+        };
+        if matches!(name, "amp" | "lt" | "gt" | "quot" | "apos") || entities.contains_key(name) {
+            continue;
+        }
+        let value = caps.get(2).or_else(|| caps.get(3)).unwrap();
+        let mut too_long = vec![];
+        let text = replacement_text(value.as_str(), &entities, &mut too_long);
+        truncated.extend(too_long.into_iter().map(|offset| value.start() + offset));
+        entities.insert(name.to_string(), text);
+    }
+    entities
+}
+
+/// The name of the reference at the start of the text, e.g. `amp` of `&amp;`.
+fn reference_name(text: &str) -> Option<&str> {
+    let len = text[1..].find(|c: char| !(c.is_alphanumeric() || matches!(c, '#' | '_' | '-' | '.' | ':')))? + 1;
+    (len > 1 && text[len..].starts_with(';')).then(|| &text[1..len])
+}
+
+fn decode_character_reference(name: &str) -> Option<char> {
+    let code = name.strip_prefix('#')?;
+    let code = match code.strip_prefix(['x', 'X']) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => code.parse().ok()?,
+    };
+    char::from_u32(code)
+}
+
+/// The replacement text of an entity value, with the character references and the entities declared earlier
+/// replaced. Like in XML, the predefined entities are left to the parsing of the markup it is inserted into.
+///
+/// The references which would make it longer than `MAX_ENTITY_LEN` are kept as text, their offsets pushed to
+/// `truncated`.
+fn replacement_text(value: &str, entities: &HashMap<String, String>, truncated: &mut Vec<usize>) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(name) = reference_name(rest) else {
+            text.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        match decode_character_reference(name) {
+            Some(c) => text.push(c),
+            None => match entities.get(name) {
+                Some(replacement) if text.len() + replacement.len() <= MAX_ENTITY_LEN => text.push_str(replacement),
+                Some(_) => {
+                    truncated.push(value.len() - rest.len());
+                    text.push_str("&amp;");
+                    text.push_str(&rest[1..name.len() + 2]);
+                },
+                None => text.push_str(&rest[..name.len() + 2]),
+            },
+        }
+        rest = &rest[name.len() + 2..];
+    }
+    text.push_str(rest);
+    text
+}
+
+/// The length of the tag at the start of the text, up to its `>` outside of the attribute values.
+fn tag_len(tag: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i + 1,
+            _ => {},
+        }
+    }
+    tag.len()
+}
+
+/// The source with the references to the declared entities replaced, and where they were.
+struct Expansion {
+    markup: String,
+    // the offsets of the replacement texts in the markup, and of their references in the source
+    replacements: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl Expansion {
+    /// The offset in the source of an offset in the markup, the reference for one in a replacement text.
+    fn source_offset(&self, offset: usize) -> usize {
+        let index = self.replacements.partition_point(|(replacement, _)| replacement.start <= offset);
+        match index.checked_sub(1).map(|index| &self.replacements[index]) {
+            None => offset,
+            Some((replacement, reference)) if offset < replacement.end => reference.start,
+            Some((replacement, reference)) => offset - replacement.end + reference.end,
+        }
+    }
+}
+
+/// Replace the references to the entities in the source after `offset`, skipping comments, CDATA and
+/// processing instructions, `None` when there is none.
+///
+/// The replacement texts are parsed as markup in the content, and only escaped in the attribute values.
+/// Fails with the offset of the reference expanding the source past `MAX_EXPANSION_LEN`.
+fn replace_entity_references(
+    src: &str,
+    offset: usize,
+    entities: &HashMap<String, String>,
+) -> Result<Option<Expansion>, usize> {
+    let mut expansion = None::<Expansion>;
+    let mut expanded_len = 0;
+    let mut rest = &src[offset..];
+    let mut copied = 0;
+    // the end of the tag the attribute values are in
+    let mut tag_end = 0;
+    while let Some(start) = rest.find(['&', '<']) {
+        let tail = &rest[start..];
+        let pos = src.len() - tail.len();
+        let in_tag = pos < tag_end;
+        let skipped = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")]
+            .into_iter()
+            .find(|(open, _)| !in_tag && tail.starts_with(open));
+        let len = match skipped {
+            Some((open, close)) => tail[open.len()..].find(close).map_or(tail.len(), |end| open.len() + end + close.len()),
+            None if tail.starts_with('<') => {
+                if !in_tag {
+                    tag_end = pos + tag_len(tail);
+                }
+                1
+            },
+            None => match reference_name(tail).and_then(|name| Some((name, entities.get(name)?))) {
+                Some((name, text)) => {
+                    expanded_len += text.len();
+                    if expanded_len > MAX_EXPANSION_LEN {
+                        return Err(pos);
+                    }
+                    let Expansion { markup, replacements } = expansion.get_or_insert_with(|| Expansion {
+                        markup: String::with_capacity(src.len()),
+                        replacements: vec![],
+                    });
+                    markup.push_str(&src[copied..pos]);
+                    let replacement = markup.len();
+                    if in_tag {
+                        for c in text.chars() {
+                            match c {
+                                '<' => markup.push_str("&lt;"),
+                                '"' => markup.push_str("&quot;"),
+                                '\'' => markup.push_str("&apos;"),
+                                c => markup.push(c),
+                            }
+                        }
+                    } else {
+                        markup.push_str(text);
+                    }
+                    copied = pos + name.len() + 2;
+                    replacements.push((replacement..markup.len(), pos..copied));
+                    name.len() + 2
+                },
+                None => 1,
+            },
+        };
+        rest = &tail[len..];
+    }
+    Ok(expansion.map(|mut expansion| {
+        expansion.markup.push_str(&src[copied..]);
+        expansion
+    }))
+}
+
+/// The source with the general entities of the internal subset expanded after the doctype,
+/// `None` when it has none, as the parser only knows the predefined entities, and the warnings about the
+/// references left in the replacement texts.
+fn expand_entities(doc: &Document, source: &Source) -> Result<(Option<Expansion>, Vec<ParseError>), ParseError> {
+    let doctype = doc.children.iter().find_map(|child| match child {
+        Child::DocumentType(doctype) => Some(doctype),
+        _ => None,
+    });
+    let Some((doctype, raw)) = doctype.and_then(|doctype| Some((doctype, doctype.raw.as_deref()?))) else {
+        return Ok((None, vec![]));
+    };
+    let Some(subset) = subset_range(raw) else {
+        return Ok((None, vec![]));
+    };
+    let mut truncated = vec![];
+    let entities = declared_entities(&raw[subset.clone()], &mut truncated);
+    // the raw doctype is its source
+    let at = |offset: usize| {
+        let pos = doctype.span.lo + BytePos(offset as u32);
+        Span::new(pos, pos, Default::default())
+    };
+    let warnings = truncated
+        .into_iter()
+        .map(|offset| {
+            let message = format!("The entity is kept as text, expanding to more than {} bytes", MAX_ENTITY_LEN);
+            source.error_at(at(subset.start + offset), message)
+        })
+        .collect();
+    if entities.is_empty() {
+        return Ok((None, warnings));
+    }
+    let offset = (doctype.span.hi - source.fm.start_pos).0 as usize;
+    let expansion = replace_entity_references(&source.fm.src, offset, &entities).map_err(|reference| {
+        let pos = source.fm.start_pos + BytePos(reference as u32);
+        let message = format!("The entities expand to more than {} bytes", MAX_EXPANSION_LEN);
+        source.error_at(Span::new(pos, pos, Default::default()), message)
+    })?;
+    Ok((expansion, warnings))
+}
+
+/// Maps the spans of the document parsed from an expansion back to the source.
+struct SpanMap<'a> {
+    expansion: &'a Expansion,
+    // the start of the expanded file, and of the source file
+    from: BytePos,
+    to: BytePos,
+}
+
+impl SpanMap<'_> {
+    fn map_pos(&self, pos: BytePos) -> BytePos {
+        let offset = self.expansion.source_offset(pos.0.saturating_sub(self.from.0) as usize);
+        self.to + BytePos(offset as u32)
+    }
+
+    fn map(&self, span: Span) -> Span {
+        if span.is_dummy() {
+            return span;
+        }
+        Span::new(self.map_pos(span.lo), self.map_pos(span.hi), span.ctxt)
+    }
+}
+
+impl VisitMut for SpanMap<'_> {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = self.map(*span);
+    }
+}
+
+/// Parse the declaration of a doctype after `<!DOCTYPE`, like `data.doctype` of the XAST.
+pub fn parse_doctype(declaration: &str) -> Option<DocumentType> {
+    let cm = Lrc::<SourceMap>::default();
+    let fm = cm.new_source_file(FileName::Anon, format!("<!DOCTYPE{}><svg/>", declaration));
+    let doc = parse_file_as_document(&fm, parser::ParserConfig::default(), &mut vec![]).ok()?;
+    doc.children.into_iter().find_map(|child| match child {
+        Child::DocumentType(doctype) => Some(DocumentType { span: DUMMY_SP, ..doctype }),
+        _ => None,
+    })
+}
+
+struct Visitor<'a> {
+    options: &'a ParseOptions,
     text_elems: Vec<&'static str>,
+    // whether the current element is in a `xml:space="preserve"` subtree
+    xml_space_preserve: bool,
+    // whether the current element is in a `<foreignObject>` subtree
+    in_foreign_object: bool,
 }
 
-impl VisitMut for Visitor {
+impl VisitMut for Visitor<'_> {
     fn visit_mut_comment(&mut self, n: &mut Comment) {
-        n.data = n.data.to_string().trim().into();
+        if !self.options.verbatim_comments {
+            n.data = n.data.trim().into();
+        }
     }
 
     fn visit_mut_element(&mut self, n: &mut Element) {
-        let mut children: Vec<Child> = vec![];
-        n.children.iter_mut().for_each(|child| {
-            if let Child::Text(text) = child {
-                if self.text_elems.contains(&n.tag_name.to_string().as_str()) {
-                    children.push(child.clone())
-                } else {
-                    let re = Regex::new(r"\S").unwrap();
-                    if re.is_match(&text.data.to_string()) {
-                        text.data = text.data.to_string().trim().into();
-                        children.push(child.clone())
+        let parent_xml_space_preserve = self.xml_space_preserve;
+        let parent_in_foreign_object = self.in_foreign_object;
+        if self.options.preserve_xml_space {
+            self.xml_space_preserve = xml_space(n).unwrap_or(self.xml_space_preserve);
+        }
+        if self.options.preserve_raw_text && &*n.tag_name == "foreignObject" {
+            self.in_foreign_object = true;
+        }
+
+        let keep_text = self.xml_space_preserve
+            || self.in_foreign_object
+            || self.text_elems.contains(&&*n.tag_name)
+            || (self.options.preserve_raw_text && matches!(&*n.tag_name, "style" | "script"));
+        if !keep_text {
+            n.children.retain_mut(|child| match child {
+                Child::Text(text) => {
+                    let trimmed = text.data.trim();
+                    if trimmed.is_empty() {
+                        return false;
                     }
-                }
-            } else {
-                children.push(child.clone())
-            }
-        });
-        n.children = children;
+                    if trimmed.len() != text.data.len() {
+                        text.data = trimmed.into();
+                    }
+                    true
+                },
+                _ => true,
+            });
+        }
 
-        n.visit_mut_children_with(self)
+        n.visit_mut_children_with(self);
+
+        self.xml_space_preserve = parent_xml_space_preserve;
+        self.in_foreign_object = parent_in_foreign_object;
     }
 }
 
-impl Visitor {
-    fn new() -> Self {
+impl<'a> Visitor<'a> {
+    fn new(options: &'a ParseOptions) -> Self {
         Self {
+            options,
             text_elems: get_text_elems(),
+            xml_space_preserve: false,
+            in_foreign_object: false,
+        }
+    }
+}
+
+/// The source of a parsed document, mapping the spans of its nodes back to lines and columns.
+#[derive(Clone)]
+pub struct Source {
+    cm: Lrc<SourceMap>,
+    fm: Lrc<SourceFile>,
+}
+
+/// Where a span starts in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// 1-based line.
+    pub line: u32,
+    /// 1-based column.
+    pub column: u32,
+    /// The source line with a caret under the column.
+    pub snippet: String,
+}
+
+impl Source {
+    fn new(input: String, path: Option<&str>) -> Self {
+        let cm = Lrc::<SourceMap>::default();
+        let file_name = match path {
+            Some(path) => FileName::Custom(path.to_string()),
+            None => FileName::Anon,
+        };
+        let fm = cm.new_source_file(file_name, input);
+        Self { cm, fm }
+    }
+
+    /// The path given to [`parse_svg`], `<anon>` without one.
+    pub fn file_name(&self) -> &FileName {
+        &self.fm.name
+    }
+
+    /// Locate a span, none for the dummy spans of nodes created by the plugins.
+    pub fn locate(&self, span: Span) -> Option<Location> {
+        if span.is_dummy() {
+            return None;
+        }
+        Some(self.locate_clamped(span))
+    }
+
+    /// Some errors are reported with a dummy span, they are located at the start or end of the file.
+    fn locate_clamped(&self, span: Span) -> Location {
+        let pos = span.lo.clamp(self.fm.start_pos, self.fm.end_pos);
+        let loc = self.cm.lookup_char_pos(pos);
+        let line = loc.line.max(1);
+        let source_line = self.fm.get_line(line - 1).unwrap_or_default();
+        Location {
+            line: line as u32,
+            column: loc.col_display as u32 + 1,
+            snippet: format!("{}\n{}^", source_line, " ".repeat(loc.col_display)),
+        }
+    }
+
+    fn to_parse_error(&self, error: Error) -> ParseError {
+        let message = error.message().to_string();
+        let (span, _) = *error.into_inner();
+        self.error_at(span, message)
+    }
+
+    fn error_at(&self, span: Span, message: String) -> ParseError {
+        let Location { line, column, snippet } = self.locate_clamped(span);
+        let path = match self.file_name() {
+            FileName::Custom(path) => Some(path.clone()),
+            _ => None,
+        };
+        ParseError {
+            message,
+            path,
+            line,
+            column,
+            snippet,
         }
     }
 }
 
-pub fn parse_svg(input: String) -> Result<Document, Error> {
-    let cm = Arc::<SourceMap>::default();
-    let fm = cm.new_source_file(FileName::Anon, input);
+pub struct ParsedSvg {
+    pub doc: Document,
+    pub source: Source,
+    /// Errors the parser recovered from.
+    pub warnings: Vec<ParseError>,
+}
+
+/// Parse an SVG, `path` naming the file in the source map and in the errors.
+///
+/// The references to the entities declared in the doctype are expanded into the attributes and the content,
+/// where their markup is parsed, the spans still pointing at the input.
+pub fn parse_svg(input: String, path: Option<&str>, options: &ParseOptions) -> Result<ParsedSvg, ParseError> {
+    let source = Source::new(input, path);
+    let (mut doc, mut warnings) = parse_document(&source, &source.fm, None)?;
+    let (expansion, entity_warnings) = expand_entities(&doc, &source)?;
+    if let Some(mut expansion) = expansion {
+        // the markup is parsed again, in a file of its own
+        let expanded = Source::new(std::mem::take(&mut expansion.markup), path);
+        let spans = SpanMap {
+            expansion: &expansion,
+            from: expanded.fm.start_pos,
+            to: source.fm.start_pos,
+        };
+        (doc, warnings) = parse_document(&source, &expanded.fm, Some(spans))?;
+    }
+    warnings.splice(0..0, entity_warnings);
+
+    let mut v = Visitor::new(options);
+    doc.visit_mut_with(&mut v);
+    Ok(ParsedSvg {
+        doc,
+        source,
+        warnings,
+    })
+}
+
+/// Parse the file into a document, `spans` mapping the spans of an expansion back to the source.
+fn parse_document(
+    source: &Source,
+    fm: &SourceFile,
+    mut spans: Option<SpanMap>,
+) -> Result<(Document, Vec<ParseError>), ParseError> {
+    let to_parse_error = |error: Error| match &spans {
+        Some(spans) => {
+            let (span, kind) = *error.into_inner();
+            source.to_parse_error(Error::new(spans.map(span), kind))
+        },
+        None => source.to_parse_error(error),
+    };
 
     let mut errors = vec![];
-    let mut r = parse_file_as_document(
-        &fm,
+    let r = parse_file_as_document(
+        fm,
         parser::ParserConfig::default(),
         &mut errors
     );
 
-    match &mut r {
-        Ok(doc) => {
-            let mut v = Visitor::new();
-            doc.visit_mut_with(&mut v);
-            r
+    match r {
+        Ok(mut doc) => {
+            let mut warnings: Vec<ParseError> = errors.into_iter().map(to_parse_error).collect();
+
+            // nothing useful was recovered
+            let has_root = doc.children.iter().any(|child| matches!(child, Child::Element(_)));
+            if !has_root && !warnings.is_empty() {
+                return Err(warnings.remove(0));
+            }
+
+            if let Some(spans) = &mut spans {
+                doc.visit_mut_with(spans);
+            }
+            Ok((doc, warnings))
         },
-        Err(_) => r
+        Err(error) => Err(to_parse_error(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    use crate::stringifier::{doctype_declaration, stringify_svg, StringifyOptions};
+    use super::*;
+
+    fn parse(input: &str, options: &ParseOptions) -> String {
+        let doc = parse_svg(input.to_string(), None, options).unwrap().doc;
+        stringify_svg(&doc, StringifyOptions::default())
+    }
+
+    #[test]
+    fn trims_text() {
+        assert_eq!(
+            parse("<svg>\n  <g>  a  </g>\n  <text>  b  </text>\n</svg>", &Default::default()),
+            "<svg><g>a</g><text>  b  </text></svg>",
+        );
+    }
+
+    #[test]
+    fn xml_space() {
+        let input = r#"<svg>
+  <g xml:space="preserve"> <a> a </a> <g xml:space="default"> <a> b </a> </g> </g>
+</svg>"#;
+        assert_eq!(
+            parse(input, &Default::default()),
+            r#"<svg><g xml:space="preserve"> <a> a </a> <g xml:space="default"><a>b</a></g> </g></svg>"#,
+        );
+        let options = ParseOptions {
+            preserve_xml_space: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse(input, &options),
+            r#"<svg><g xml:space="preserve"><a>a</a><g xml:space="default"><a>b</a></g></g></svg>"#,
+        );
+    }
+
+    #[test]
+    fn raw_text() {
+        let input = "<svg>\n<style>\n  a {}\n</style>\n<foreignObject>\n  <p> c </p>\n</foreignObject>\n</svg>";
+        assert_eq!(parse(input, &Default::default()), "<svg><style>a {}</style><foreignObject><p>c</p></foreignObject></svg>");
+        let options = ParseOptions {
+            preserve_raw_text: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse(input, &options),
+            "<svg><style>\n  a {}\n</style><foreignObject>\n  <p> c </p>\n</foreignObject></svg>",
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let input = "<svg><!--  a  --></svg>";
+        assert_eq!(parse(input, &Default::default()), "<svg><!--a--></svg>");
+        let options = ParseOptions {
+            verbatim_comments: true,
+            ..Default::default()
+        };
+        assert_eq!(parse(input, &options), "<svg><!--  a  --></svg>");
+    }
+
+    #[test]
+    fn doctype() {
+        let declaration = r#" svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "svg[1].dtd" [ <!ENTITY a "b"> ]"#;
+        let doctype = parse_doctype(declaration).unwrap();
+        assert_eq!(doctype.name.as_deref(), Some("svg"));
+        assert_eq!(doctype.system_id.as_deref(), Some("svg[1].dtd"));
+        assert_eq!(internal_subset(&doctype), Some(r#" <!ENTITY a "b"> "#));
+        assert_eq!(doctype_declaration(&doctype), declaration);
+    }
+
+    #[test]
+    fn entities() {
+        let input = r#"<!DOCTYPE svg [
+  <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+  <!-- <!ENTITY a "commented"> -->
+  <!ENTITY a 'x &amp; "&ns_svg;"'>
+  <!ENTITY b "&#38;#60;&amp;c;">
+]>
+<svg xmlns="&ns_svg;" a="&a;"><text>&a; &amp;a; &b; &lt;</text><![CDATA[&a;]]><!--&a;--></svg>"#;
+        let parsed = parse_svg(input.to_string(), None, &Default::default()).unwrap();
+        assert!(parsed.warnings.is_empty());
+        let svg = stringify_svg(&parsed.doc, StringifyOptions::default());
+        assert_eq!(
+            &svg[svg.find("<svg").unwrap()..],
+            r#"<svg xmlns="http://www.w3.org/2000/svg" a='x &amp; "http://www.w3.org/2000/svg"'><text>x &amp; &quot;http://www.w3.org/2000/svg&quot; &amp;a; &lt;&amp;c; &lt;</text><![CDATA[&a;]]><!--&a;--></svg>"#,
+        );
+    }
+
+    #[test]
+    fn entity_spans() {
+        let input = format!(
+            "<!DOCTYPE svg [ <!ENTITY l \"{}\"> ]>\n<svg xmlns=\"http://www.w3.org/2000/svg\">\n<rect class=\"&l;\"/><circle/>\n</svg>",
+            "a".repeat(40),
+        );
+        let parsed = parse_svg(input, Some("ent.svg"), &Default::default()).unwrap();
+        let Some(Child::Element(svg)) = parsed.doc.children.last() else {
+            panic!("no root element");
+        };
+        let locations: Vec<_> = svg
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Child::Element(element) => parsed.source.locate(element.span),
+                _ => None,
+            })
+            .map(|location| (location.line, location.column))
+            .collect();
+        assert_eq!(locations, vec![(3, 1), (3, 20)]);
+    }
+
+    #[test]
+    fn entity_markup() {
+        let input = "<!DOCTYPE svg [ <!ENTITY e '<g fill=\"red\"><rect/></g>'> ]>\n<svg a=\"&e;\">\n  &e;</svg>";
+        let parsed = parse_svg(input.to_string(), None, &Default::default()).unwrap();
+        assert!(parsed.warnings.is_empty());
+        let Some(Child::Element(svg)) = parsed.doc.children.last() else {
+            panic!("no root element");
+        };
+        let Some(Child::Element(g)) = svg.children.first() else {
+            panic!("no element from the entity");
+        };
+        let location = parsed.source.locate(g.span).unwrap();
+        assert_eq!((location.line, location.column), (3, 3));
+        let svg = stringify_svg(&parsed.doc, StringifyOptions::default());
+        assert_eq!(
+            &svg[svg.find("<svg").unwrap()..],
+            r#"<svg a='&lt;g fill="red"&gt;&lt;rect/&gt;&lt;/g&gt;'><g fill="red"><rect/></g></svg>"#,
+        );
+    }
+
+    #[test]
+    fn entity_length_limit() {
+        let input = format!(
+            "<!DOCTYPE svg [\n  <!ENTITY a \"{}\">\n  <!ENTITY b \"&a;&a;\">\n]>\n<svg>&b;</svg>",
+            "a".repeat(MAX_ENTITY_LEN / 2 + 1),
+        );
+        let parsed = parse_svg(input, None, &Default::default()).unwrap();
+        assert_eq!(parsed.warnings.len(), 1);
+        let warning = &parsed.warnings[0];
+        assert_eq!(
+            warning.message,
+            format!("The entity is kept as text, expanding to more than {} bytes", MAX_ENTITY_LEN),
+        );
+        assert_eq!((warning.line, warning.column), (3, 18));
+        let svg = stringify_svg(&parsed.doc, StringifyOptions::default());
+        assert!(svg.ends_with("a&amp;a;</svg>"));
+    }
+
+    #[test]
+    fn entity_expansion_limit() {
+        let input = format!(
+            "<!DOCTYPE svg [ <!ENTITY a \"{}\"> ]>\n<svg>{}</svg>",
+            "a".repeat(MAX_ENTITY_LEN),
+            "&a;".repeat(MAX_EXPANSION_LEN / MAX_ENTITY_LEN + 1),
+        );
+        let error = parse_svg(input, None, &Default::default()).err().unwrap();
+        assert_eq!(error.message, format!("The entities expand to more than {} bytes", MAX_EXPANSION_LEN));
+        assert_eq!((error.line, error.column), (2, 6 + 3 * (MAX_EXPANSION_LEN / MAX_ENTITY_LEN) as u32));
     }
 }
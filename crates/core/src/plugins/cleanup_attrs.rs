@@ -3,14 +3,33 @@
 use swc_xml_ast::*;
 use swc_xml_visit::{VisitMut, VisitMutWith};
 use regex::{Regex, Captures};
+use serde::Deserialize;
+
+use super::{Context, Plugin};
+
+#[derive(Debug, Deserialize)]
+pub struct Params {
+    #[serde(default = "default_newlines")]
+    pub newlines: bool,
+    #[serde(default = "default_trim")]
+    pub trim: bool,
+    #[serde(default = "default_spaces")]
+    pub spaces: bool,
+}
 
-struct Visitor {
-    newlines: bool,
-    trim: bool,
-    spaces: bool,
+fn default_newlines() -> bool {
+    true
+}
+
+fn default_trim() -> bool {
+    true
 }
 
-impl Default for Visitor {
+fn default_spaces() -> bool {
+    true
+}
+
+impl Default for Params {
     fn default() -> Self {
         Self {
             newlines: true,
@@ -20,6 +39,32 @@ impl Default for Visitor {
     }
 }
 
+struct Visitor {
+    newlines: bool,
+    trim: bool,
+    spaces: bool,
+
+    reg_newlines_need_space: Regex,
+    reg_new_lines: Regex,
+    reg_spaces: Regex,
+}
+
+impl Visitor {
+    fn new(params: &Params) -> Self {
+        Self {
+            newlines: params.newlines,
+            trim: params.trim,
+            spaces: params.spaces,
+
+            // new line which requires a space instead of themselve
+            reg_newlines_need_space: Regex::new(r#"(\S)\r?\n(\S)"#).unwrap(),
+            // simple new line
+            reg_new_lines: Regex::new(r#"\r?\n"#).unwrap(),
+            reg_spaces: Regex::new(r#"\s{2,}"#).unwrap(),
+        }
+    }
+}
+
 impl VisitMut for Visitor {
     fn visit_mut_element(&mut self, n: &mut Element) {
         n.visit_mut_children_with(self);
@@ -32,13 +77,9 @@ impl VisitMut for Visitor {
             let mut value = attr.value.clone().unwrap().to_string();
 
             if self.newlines {
-                // new line which requires a space instead of themselve
-                let reg_newlines_need_space = Regex::new(r#"(\S)\r?\n(\S)"#).unwrap();
-                value = reg_newlines_need_space.replace_all(&value, |caps: &Captures| format!("{} {}", &caps[1], &caps[2])).to_string();
+                value = self.reg_newlines_need_space.replace_all(&value, |caps: &Captures| format!("{} {}", &caps[1], &caps[2])).to_string();
 
-                // simple new line
-                let reg_new_lines = Regex::new(r#"\r?\n"#).unwrap();
-                value = reg_new_lines.replace_all(&value, |_: &Captures| "").to_string()
+                value = self.reg_new_lines.replace_all(&value, |_: &Captures| "").to_string()
             }
 
             if self.trim {
@@ -46,8 +87,7 @@ impl VisitMut for Visitor {
             }
 
             if self.spaces {
-                let reg_spaces = Regex::new(r#"\s{2,}"#).unwrap();
-                value = reg_spaces.replace_all(&value, |_: &Captures| " ").to_string()
+                value = self.reg_spaces.replace_all(&value, |_: &Captures| " ").to_string()
             }
 
             attr.value = Some(value.into());
@@ -55,9 +95,23 @@ impl VisitMut for Visitor {
     }
 }
 
-pub fn apply(doc: &mut Document) {
-    let mut v: Visitor = Default::default();
-    doc.visit_mut_with(&mut v);
+pub struct CleanupAttrs;
+
+impl Plugin for CleanupAttrs {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "cleanupAttrs"
+    }
+
+    fn description(&self) -> &str {
+        "cleanups attributes from newlines, trailing and repeating spaces"
+    }
+
+    fn apply(&self, doc: &mut Document, params: &Params, _: &mut Context) {
+        let mut v = Visitor::new(params);
+        doc.visit_mut_with(&mut v);
+    }
 }
 
 #[cfg(test)]
@@ -70,8 +124,8 @@ mod tests {
     use super::*;
 
     fn code_test(input: String, expected: String) {
-        let mut doc = parse_svg(input).unwrap();
-        apply(&mut doc);
+        let mut doc = parse_svg(input, None, &Default::default()).unwrap().doc;
+        CleanupAttrs.apply(&mut doc, &Default::default(), &mut Default::default());
         let result = stringify_svg(&doc, StringifyOptions {
             pretty: true,
             ..Default::default()
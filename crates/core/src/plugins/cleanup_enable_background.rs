@@ -10,23 +10,40 @@
 use swc_xml_ast::*;
 use swc_xml_visit::{VisitMut, Visit, VisitWith, VisitMutWith};
 use regex::Regex;
+use serde::Deserialize;
+
+use crate::namespaces::Scope;
+use super::{Context, Plugin};
+
+#[derive(Debug, Deserialize, Default)]
+pub struct Params {
+}
 
 struct Visitor {
     has_filter: bool,
+    scope: Scope,
 }
 
 impl Visitor {
     pub fn new(has_filter: bool) -> Visitor {
         Self {
             has_filter,
+            scope: Scope::new(),
         }
     }
 }
 
 impl VisitMut for Visitor {
     fn visit_mut_element(&mut self, n: &mut Element) {
+        self.scope.enter(n);
         n.visit_mut_children_with(self);
+        self.cleanup(n);
+        self.scope.leave();
+    }
+}
 
+impl Visitor {
+    fn cleanup(&self, n: &mut Element) {
         let enable_background_index = n.attributes.iter().position(|attr| attr.name.to_string() == "enable-background");
         if enable_background_index.is_none() {
             return;
@@ -35,25 +52,24 @@ impl VisitMut for Visitor {
         let enable_background_index = enable_background_index.unwrap();
 
         if self.has_filter {
-            let tag_name = n.tag_name.to_string();
+            let tag_name = self.scope.svg_name(n).unwrap_or_default().to_string();
 
             let height_index = n.attributes.iter().position(|attr| attr.name.to_string() == "height" && attr.value.is_some());
             let width_index = n.attributes.iter().position(|attr| attr.name.to_string() == "width" && attr.value.is_some());
 
-            if (tag_name == "svg" || tag_name == "mask" || tag_name == "pattern") && height_index.is_some() && width_index.is_some() {
+            let is_sized = tag_name == "svg" || tag_name == "mask" || tag_name == "pattern";
+            if let (true, Some(height_index), Some(width_index)) = (is_sized, height_index, width_index) {
                 let value = match n.attributes[enable_background_index].value {
                     Some(ref value) => value.to_string(),
                     None => "".to_string(),
                 };
-                let height = n.attributes[height_index.unwrap()].value.clone().unwrap().to_string();
-                let width = n.attributes[width_index.unwrap()].value.clone().unwrap().to_string();
+                let height = n.attributes[height_index].value.clone().unwrap().to_string();
+                let width = n.attributes[width_index].value.clone().unwrap().to_string();
 
                 let reg_enable_background = Regex::new(r#"^new\s0\s0\s([-+]?\d*\.?\d+([eE][-+]?\d+)?)\s([-+]?\d*\.?\d+([eE][-+]?\d+)?)$"#).unwrap();
                 let captures = reg_enable_background.captures(&value);
 
-                if captures.is_some() {
-                    let captures = captures.unwrap();
-
+                if let Some(captures) = captures {
                     if captures[1] == width && captures[3] == height {
                         if tag_name == "svg" {
                             n.attributes.remove(enable_background_index);
@@ -70,34 +86,44 @@ impl VisitMut for Visitor {
     }
 }
 
+#[derive(Default)]
 pub struct FilterVisitor {
     has_filter: bool,
-}
-
-impl Default for FilterVisitor {
-    fn default() -> Self {
-        Self {
-            has_filter: false,
-        }
-    }
+    scope: Scope,
 }
 
 impl Visit for FilterVisitor {
     fn visit_element(&mut self, n: &Element) {
-        if n.tag_name.to_string() == "filter" {
+        if self.scope.is_svg_element(n, "filter") {
             self.has_filter = true
         } else {
+            self.scope.enter(n);
             n.visit_children_with(self);
+            self.scope.leave();
         }
     }
 }
 
-pub fn apply(doc: &mut Document) {
-    let mut filter_visitor: FilterVisitor = Default::default();
-    doc.visit_with(&mut filter_visitor);
+pub struct CleanupEnableBackground;
+
+impl Plugin for CleanupEnableBackground {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "cleanupEnableBackground"
+    }
 
-    let mut v = Visitor::new(filter_visitor.has_filter);
-    doc.visit_mut_with(&mut v);
+    fn description(&self) -> &str {
+        "remove or cleanup enable-background attribute when possible"
+    }
+
+    fn apply(&self, doc: &mut Document, _: &Params, _: &mut Context) {
+        let mut filter_visitor: FilterVisitor = Default::default();
+        doc.visit_with(&mut filter_visitor);
+
+        let mut v = Visitor::new(filter_visitor.has_filter);
+        doc.visit_mut_with(&mut v);
+    }
 }
 
 #[cfg(test)]
@@ -110,8 +136,8 @@ mod tests {
     use super::*;
 
     fn code_test(input: String, expected: String) {
-        let mut doc = parse_svg(input).unwrap();
-        apply(&mut doc);
+        let mut doc = parse_svg(input, None, &Default::default()).unwrap().doc;
+        CleanupEnableBackground.apply(&mut doc, &Default::default(), &mut Default::default());
         let result = stringify_svg(&doc, StringifyOptions {
             pretty: true,
             ..Default::default()
@@ -3,6 +3,7 @@
 
 use std::collections::{HashMap, HashSet};
 
+use swc_core::common::Span;
 use swc_xml_ast::*;
 use swc_xml_visit::{VisitMut, VisitMutWith};
 use regex::Regex;
@@ -10,15 +11,23 @@ use linked_hash_map::LinkedHashMap;
 use serde::Deserialize;
 
 use crate::collections;
+use crate::namespaces::Scope;
+use super::{Context, Plugin};
 
 struct EnterVisitor<'a> {
     // Options
     force: bool,
 
     // States
+    scope: Scope,
     references_props: Vec<&'static str>,
+    reg_references_url: Regex,
+    reg_references_href: Regex,
+    reg_references_begin: Regex,
     deoptimized: bool,
     node_by_id: LinkedHashMap<String, &'a mut Element>,
+    // removed repeated IDs and the elements they were removed from
+    duplicates: Vec<(String, Span)>,
     references_by_id: HashMap<String, Vec<(&'a mut Element, String, String)>>,
 }
 
@@ -32,9 +41,14 @@ impl EnterVisitor<'_> {
         Self {
             force,
 
+            scope: Scope::new(),
             references_props: collections::get_references_props(),
+            reg_references_url: Regex::new(r#"\burl\((["'])?#(.+?)(["'])?\)"#).unwrap(),
+            reg_references_href: Regex::new(r#"^#(.+?)$"#).unwrap(),
+            reg_references_begin: Regex::new(r#"(\D+)\."#).unwrap(),
             deoptimized: false,
             node_by_id: LinkedHashMap::new(),
+            duplicates: vec![],
             references_by_id: HashMap::new(),
         }
     }
@@ -42,10 +56,18 @@ impl EnterVisitor<'_> {
 
 impl VisitMut for EnterVisitor<'_> {
     fn visit_mut_element(&mut self, n: &mut Element) {
-        let tag_name = n.tag_name.to_string();
+        self.scope.enter(n);
+        self.enter_element(n);
+        self.scope.leave();
+    }
+}
+
+impl EnterVisitor<'_> {
+    fn enter_element(&mut self, n: &mut Element) {
+        let tag_name = self.scope.svg_name(n).unwrap_or_default().to_string();
 
-        if self.force == false {
-            if (tag_name == "style" ||tag_name == "script") && n.children.len() != 0 {
+        if !self.force {
+            if (tag_name == "style" ||tag_name == "script") && !n.children.is_empty() {
                 self.deoptimized = true;
 
                 n.visit_mut_children_with(self);        
@@ -56,7 +78,7 @@ impl VisitMut for EnterVisitor<'_> {
             if tag_name == "svg" {
                 let has_defs_only = !n.children.iter().any(|child| {
                     match child {
-                        Child::Element(child) => child.tag_name.to_string() != "defs",
+                        Child::Element(child) => !self.scope.is_svg_element(child, "defs"),
                         Child::Text(child) => child.data.to_string().trim() != "",
                         _ => true,
                     }
@@ -79,6 +101,7 @@ impl VisitMut for EnterVisitor<'_> {
                 // collect all ids
                 if self.node_by_id.contains_key(value) {
                     n.attributes.remove(index); // remove repeated id
+                    self.duplicates.push((value.to_string(), n.span));
                 } else {
                     unsafe {
                         let r = n as *mut Element;
@@ -88,26 +111,14 @@ impl VisitMut for EnterVisitor<'_> {
             } else {
                 // collect all references
                 let id = if self.references_props.contains(&name.as_str()) {
-                    let reg_references_url = Regex::new(r#"\burl\((["'])?#(.+?)(["'])?\)"#).unwrap();
-                    let captures = reg_references_url.captures(&value);
-                    match captures {
-                        Some(captures) => Some(captures.get(2).unwrap().as_str()), // url() reference
-                        None => None,
-                    }
-                } else if name == "href" || name.ends_with(":href") {
-                    let reg_references_href = Regex::new(r#"^#(.+?)$"#).unwrap();
-                    let captures = reg_references_href.captures(&value);
-                    match captures {
-                        Some(captures) => Some(captures.get(1).unwrap().as_str()), // href reference
-                        None => None,
-                    }
+                    let captures = self.reg_references_url.captures(value);
+                    captures.map(|captures| captures.get(2).unwrap().as_str())
+                } else if self.scope.is_href(&attr) {
+                    let captures = self.reg_references_href.captures(value);
+                    captures.map(|captures| captures.get(1).unwrap().as_str())
                 } else if name == "begin" {
-                    let reg_references_begin = Regex::new(r#"(\D+)\."#).unwrap();
-                    let captures = reg_references_begin.captures(&value);
-                    match captures {
-                        Some(captures) => Some(captures.get(1).unwrap().as_str()), // href reference
-                        None => None,
-                    }
+                    let captures = self.reg_references_begin.captures(value);
+                    captures.map(|captures| captures.get(1).unwrap().as_str())
                 } else {
                     None
                 };
@@ -138,7 +149,7 @@ impl VisitMut for EnterVisitor<'_> {
 }
 
 // Check if an ID starts with any one of a list of strings.
-fn has_string_prefix(str: &String, prefixes: &Vec<String>) -> bool {
+fn has_string_prefix(str: &str, prefixes: &[String]) -> bool {
     for prefix in prefixes {
         if str.starts_with(prefix.as_str()) {
             return true
@@ -238,7 +249,25 @@ impl Default for Params {
     }
 }
 
-pub fn apply(doc: &mut Document, params: &Params) {
+pub struct CleanupIds;
+
+impl Plugin for CleanupIds {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "cleanupIds"
+    }
+
+    fn description(&self) -> &str {
+        "removes unused IDs and minifies used"
+    }
+
+    fn apply(&self, doc: &mut Document, params: &Params, context: &mut Context) {
+        apply(doc, params, context)
+    }
+}
+
+fn apply(doc: &mut Document, params: &Params, context: &mut Context) {
     let Params {
         remove,
         minify,
@@ -250,13 +279,18 @@ pub fn apply(doc: &mut Document, params: &Params) {
     let mut v = EnterVisitor::new(params);
     doc.visit_mut_with(&mut v);
 
+    for (id, span) in v.duplicates.drain(..) {
+        context.warn_at(span, format!("Removed duplicate ID \"{}\"", id));
+    }
+
     if v.deoptimized {
+        context.warn("IDs are not cleaned up because of <style> or <script>, set \"force\" to clean them up anyway");
         return;
     }
 
-    let preserve_ids: HashSet<String> = preserve.iter().map(|x| x.clone()).collect();
+    let preserve_ids: HashSet<String> = preserve.iter().cloned().collect();
 
-    let is_id_preserved = |id: &String| preserve_ids.get(id).is_some() || has_string_prefix(id, preserve_prefixes);
+    let is_id_preserved = |id: &String| preserve_ids.contains(id) || has_string_prefix(id, preserve_prefixes);
 
     let generate_id_chars = get_generate_id_chars();
     let max_id_index: usize = generate_id_chars.len() - 1;
@@ -311,13 +345,13 @@ pub fn apply(doc: &mut Document, params: &Params) {
                     for (element, name, value) in refs {
                         if value.contains('#') {
                             // replace id in href and url()
-                            let attr = element.attributes.iter_mut().find(|attr| attr.name.to_string() == *name);
+                            let attr = element.attributes.iter_mut().find(|attr| attr.name == *name);
                             if let Some(attr) = attr {
                                 attr.value = Some(value.replace(&format!("#{}", id), &format!("#{}", current_id_string)).into());
                             }
                         } else {
                             // replace id in begin attribute
-                            let attr = element.attributes.iter_mut().find(|attr| attr.name.to_string() == *name);
+                            let attr = element.attributes.iter_mut().find(|attr| attr.name == *name);
                             if let Some(attr) = attr {
                                 attr.value = Some(value.replace(&format!("{}.", id), &format!("{}.", current_id_string)).into());
                             }
@@ -334,7 +368,7 @@ pub fn apply(doc: &mut Document, params: &Params) {
     // remove non-referenced IDs attributes from elements
     if *remove {
         for (id, n) in non_referenced_ids {
-            if !is_id_preserved(&id) {
+            if !is_id_preserved(id) {
                 let index = n.attributes.iter().position(|attr| attr.name.to_string() == "id");
                 if let Some(index) = index {
                     n.attributes.remove(index);
@@ -346,44 +380,12 @@ pub fn apply(doc: &mut Document, params: &Params) {
 
 #[cfg(test)]
 mod tests {
-    use std::{path::PathBuf, fs};
-
-    #[cfg(test)]
-    use pretty_assertions::assert_eq;
-
-    use crate::parser::parse_svg;
-    use crate::stringifier::{stringify_svg, StringifyOptions};
-    use super::*;
-
-    fn code_test(input: String, expected: String, params: &Params) {
-        let mut doc = parse_svg(input).unwrap();
-        apply(&mut doc, &params);
-        let result = stringify_svg(&doc, StringifyOptions {
-            pretty: true,
-            ..Default::default()
-        });
-        assert_eq!(result.trim_end(), expected);
-    }
-
-    fn document_test(input: PathBuf) {
-        let text = fs::read_to_string(input).unwrap();
-        let re = Regex::new(r"\s*@@@\s*").unwrap();
-        let fields: Vec<&str> = re.split(&text).collect();
+    use std::path::PathBuf;
 
-        let input = fields[0].trim();
-        let expected = fields[1].trim();
-        let params: Params = if fields.len() > 2 {
-            let json_str = fields[2].trim();
-            serde_json::from_str(json_str).unwrap()
-        } else {
-            Default::default()
-        };
-
-        code_test(input.to_string(), expected.to_string(), &params);
-    }
+    use crate::testing::test_plugin;
 
     #[testing::fixture("__fixture__/plugins/cleanupIds*.svg")]
     fn pass(input: PathBuf) {
-        document_test(input);
+        test_plugin("cleanupIds", input);
     }
 }
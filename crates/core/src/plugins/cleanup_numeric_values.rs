@@ -8,6 +8,9 @@ use swc_xml_visit::{VisitMut, VisitMutWith};
 use regex::Regex;
 use serde::Deserialize;
 
+use crate::namespaces::Scope;
+use super::{Context, Plugin};
+
 // relative to px
 fn get_absolute_lengths() -> HashMap<&'static str, f64> {
     HashMap::from([
@@ -43,7 +46,7 @@ fn round_value(value: &str, float_precision: i32) -> String {
 fn remove_leading_zero(num: f64) -> String {
     let mut str_num = num.to_string();
 
-    if 0.0 < num && num < 1.0 && str_num.chars().next() == Some('0') {
+    if 0.0 < num && num < 1.0 && str_num.starts_with('0') {
         str_num = str_num[1..].to_string();
     } else if -1.0 < num && num < 0.0 && str_num.chars().nth(1) == Some('0') {
         str_num = str_num.chars().take(1).chain(str_num.chars().skip(2)).collect();
@@ -53,6 +56,7 @@ fn remove_leading_zero(num: f64) -> String {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Params {
     #[serde(default = "default_float_precision")]
     pub float_precision: i32,
@@ -93,23 +97,31 @@ impl Default for Params {
 
 struct Visitor<'a> {
     params: &'a Params,
+    scope: Scope,
 }
 
 impl<'a> Visitor<'a> {
     fn new(params: &'a Params) -> Self {
         Self {
             params,
+            scope: Scope::new(),
         }
     }
 }
 
 impl VisitMut for Visitor<'_> {
+    fn visit_mut_element(&mut self, n: &mut Element) {
+        self.scope.enter(n);
+        n.visit_mut_children_with(self);
+        self.scope.leave();
+    }
+
     fn visit_mut_attribute(&mut self, n: &mut Attribute) {
-        if n.name.to_string() == "viewBox" {
+        if self.scope.is_attr(n, None, "viewBox") {
             if let Some(value) = n.value.clone() {
-                let nums: Vec<String> = value.to_string()
+                let nums: Vec<String> = value
                     .split(|c: char| c.is_whitespace() || c == ',')
-                    .filter(|s| *s != "")
+                    .filter(|s| !s.is_empty())
                     .map(|s| s.to_string())
                     .collect();
                 let rounded_nums: Vec<String> = nums
@@ -120,13 +132,13 @@ impl VisitMut for Visitor<'_> {
             }
         }
 
-        if n.name.to_string() == "version" {
+        if self.scope.is_attr(n, None, "version") {
             return;
         }
 
         if let Some(value) = n.value.clone() {
             let reg_numeric_values = Regex::new(r#"^([-+]?\d*\.?\d+([eE][-+]?\d+)?)(px|pt|pc|mm|cm|m|in|ft|em|ex|%)?$"#).unwrap();
-            if let Some(captures) = reg_numeric_values.captures(&value.to_string()) {
+            if let Some(captures) = reg_numeric_values.captures(value.as_ref()) {
                 let num_str = captures.get(1).map_or("", |m| m.as_str());
                 let unit = captures.get(3).map_or("", |m| m.as_str());
 
@@ -165,9 +177,27 @@ impl VisitMut for Visitor<'_> {
     }
 }
 
-pub fn apply(doc: &mut Document, params: &Params) {
-    let mut v: Visitor = Visitor::new(params);
-    doc.visit_mut_with(&mut v);
+pub struct CleanupNumericValues;
+
+impl Plugin for CleanupNumericValues {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "cleanupNumericValues"
+    }
+
+    fn description(&self) -> &str {
+        "rounds numeric values to the fixed precision, removes default ‘px’ units"
+    }
+
+    fn precision_param(&self) -> Option<&str> {
+        Some("floatPrecision")
+    }
+
+    fn apply(&self, doc: &mut Document, params: &Params, _: &mut Context) {
+        let mut v: Visitor = Visitor::new(params);
+        doc.visit_mut_with(&mut v);
+    }
 }
 
 #[cfg(test)]
@@ -175,10 +205,9 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::testing::test_plugin;
-    use super::*;
 
     #[testing::fixture("__fixture__/plugins/cleanupNumericValues*.svg")]
     fn pass(input: PathBuf) {
-        test_plugin(apply, input);
+        test_plugin("cleanupNumericValues", input);
     }
 }
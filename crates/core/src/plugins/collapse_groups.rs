@@ -23,12 +23,15 @@ use swc_xml_visit::{VisitMut, VisitMutWith};
 use serde::Deserialize;
 
 use crate::collections::{get_elems_groups, get_inheritable_attrs};
+use crate::namespaces::Scope;
+use super::{Context, Plugin};
 
 #[derive(Debug, Deserialize, Default)]
 pub struct Params {
 }
 
 struct Visitor {
+    scope: Scope,
     elems_groups: HashMap<&'static str, Vec<&'static str>>,
     inheritable_attrs: Vec<&'static str>,
 }
@@ -36,6 +39,7 @@ struct Visitor {
 impl Default for Visitor {
     fn default() -> Self {
         Self {
+            scope: Scope::new(),
             elems_groups: get_elems_groups(),
             inheritable_attrs: get_inheritable_attrs(),
         }
@@ -43,8 +47,14 @@ impl Default for Visitor {
 }
 
 impl Visitor {
-    fn has_animated_attr(&mut self, n: &Element, name: &str) -> bool {
-        if self.elems_groups.get("animation").unwrap().contains(&n.tag_name.to_string().as_str()) &&
+    fn is_animation(&self, n: &Element) -> bool {
+        self.scope
+            .svg_name(n)
+            .is_some_and(|name| self.elems_groups.get("animation").unwrap().contains(&name))
+    }
+
+    fn has_animated_attr(&self, n: &Element, name: &str) -> bool {
+        if self.is_animation(n) &&
             n.attributes.iter().any(|attr| attr.name.to_string() == "attributeName" && attr.value == Some(name.into()))
         {
             return true;
@@ -62,21 +72,28 @@ impl Visitor {
 
 impl VisitMut for Visitor {
     fn visit_mut_element(&mut self, p: &mut Element) {
+        self.scope.enter(p);
         p.visit_mut_children_with(self);
+        self.collapse(p);
+        self.scope.leave();
+    }
+}
 
-        if p.tag_name.to_string() == "switch" {
+impl Visitor {
+    fn collapse(&self, p: &mut Element) {
+        if self.scope.is_svg_element(p, "switch") {
             return;
         }
 
         p.children.iter_mut().for_each(|n| {
             if let Child::Element(n) = n {
                 // non-empty groups
-                if n.tag_name.to_string() != "g" || n.children.len() == 0 {
+                if !self.scope.is_svg_element(n, "g") || n.children.is_empty() {
                     return;
                 }
 
                 // move group attibutes to the single child element
-                if n.attributes.len() != 0 && n.children.len() == 1 {
+                if !n.attributes.is_empty() && n.children.len() == 1 {
                     let mut n_attrs:HashSet::<String> = HashSet::new();
                     n.attributes.iter().for_each(|attr: &Attribute| {
                         n_attrs.insert(attr.name.to_string());
@@ -99,7 +116,7 @@ impl VisitMut for Visitor {
                                 !first_child_attrs.contains("class")) &&
                             ((!n_attrs.contains("clip-path") &&
                                 !n_attrs.contains("mask")) ||
-                                (first_child.tag_name.to_string() == "g" &&
+                                (self.scope.is_svg_element(first_child, "g") &&
                                     !n_attrs.contains("transform") &&
                                     !first_child_attrs.contains("transform")))
                         {
@@ -108,14 +125,14 @@ impl VisitMut for Visitor {
                                 let name = attr.name.to_string();
                                 if let Some(value) = attr.value.clone() {
                                     // avoid copying to not conflict with animated attribute
-                                    if self.has_animated_attr(&first_child, &name) {
+                                    if self.has_animated_attr(first_child, &name) {
                                         return;
                                     }
 
                                     let first_child_attr = first_child
                                         .attributes
                                         .iter_mut()
-                                        .find(|attr| attr.name.to_string() == name);
+                                        .find(|attr| attr.name == name);
                                     match first_child_attr {
                                         None => {
                                             first_child.attributes.push(Attribute {
@@ -130,7 +147,7 @@ impl VisitMut for Visitor {
                                         },
                                         Some(first_child_attr) => {
                                             if name == "transform" {
-                                                let new_value = format!("{} {}", value, first_child_attr.value.clone().unwrap_or("".into()).to_string());
+                                                let new_value = format!("{} {}", value, first_child_attr.value.clone().unwrap_or("".into()));
                                                 first_child_attr.value = Some(new_value.into());
                                             } else if first_child_attr.value == Some("inherit".into()) {
                                                 first_child_attr.value = Some(value);
@@ -153,18 +170,18 @@ impl VisitMut for Visitor {
         let mut new_children: Vec<Child> = vec![];
         for child in p.children.iter() {
             if let Child::Element(n) = child {
-                if n.tag_name.to_string() != "g" || n.children.len() == 0 {
+                if !self.scope.is_svg_element(n, "g") || n.children.is_empty() {
                     new_children.push(child.clone());
                     continue;
                 }
 
                 // collapse groups without attributes
-                if n.tag_name.to_string() != "g" || n.attributes.len() == 0 {
+                if !self.scope.is_svg_element(n, "g") || n.attributes.is_empty() {
                     // animation elements "add" attributes to group
                     // group should be preserved
                     for child in n.children.iter() {
                         if let Child::Element(child) = child {
-                            if self.elems_groups.get("animation").unwrap().contains(&child.tag_name.to_string().as_str()) {
+                            if self.is_animation(child) {
                                 return;
                             }
                         }
@@ -182,9 +199,23 @@ impl VisitMut for Visitor {
     }
 }
 
-pub fn apply(doc: &mut Document, _: &Params) {
-    let mut v: Visitor = Default::default();
-    doc.visit_mut_with(&mut v);
+pub struct CollapseGroups;
+
+impl Plugin for CollapseGroups {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "collapseGroups"
+    }
+
+    fn description(&self) -> &str {
+        "collapses useless groups"
+    }
+
+    fn apply(&self, doc: &mut Document, _: &Params, _: &mut Context) {
+        let mut v: Visitor = Default::default();
+        doc.visit_mut_with(&mut v);
+    }
 }
 
 #[cfg(test)]
@@ -192,10 +223,9 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::testing::test_plugin;
-    use super::*;
 
     #[testing::fixture("__fixture__/plugins/collapseGroups.*.svg")]
     fn pass(input: PathBuf) {
-        test_plugin(apply, input);
+        test_plugin("collapseGroups", input);
     }
 }
@@ -8,6 +8,8 @@ use swc_xml_visit::{VisitMut, VisitMutWith};
 use serde::Deserialize;
 
 use crate::collections::{get_colors_props, get_colors_names, get_colors_short_names};
+use crate::namespaces::Scope;
+use super::{Context, Plugin};
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
@@ -17,7 +19,7 @@ pub enum CurrentColor {
 }
 
 /// Convert [r, g, b] to #rrggbb.
-fn convert_rgb_to_hex(rgb: &Vec<u8>) -> String {
+fn convert_rgb_to_hex(rgb: &[u8]) -> String {
     let hex_number =
         (u32::from(rgb[0]) << 16) // [r]
         | (u32::from(rgb[1]) << 8) // [r][g]
@@ -90,7 +92,9 @@ struct Visitor<'a> {
     // Collections
     colors_names: HashMap<&'static str, &'static str>,
     colors_short_names: HashMap<&'static str, &'static str>,
-    colors_props: Vec<&'static str>
+    colors_props: Vec<&'static str>,
+
+    scope: Scope,
 }
 
 impl<'a> Visitor<'a> {
@@ -100,13 +104,14 @@ impl<'a> Visitor<'a> {
             colors_names: get_colors_names(),
             colors_short_names: get_colors_short_names(),
             colors_props: get_colors_props(),
+            scope: Scope::new(),
         }
     }
 }
 
 impl VisitMut for Visitor<'_> {
     fn visit_mut_attribute(&mut self, n: &mut Attribute) {
-        if !self.colors_props.contains(&n.name.to_string().as_str()) {
+        if !self.colors_props.iter().any(|prop| self.scope.is_attr(n, None, prop)) {
             return;
         }
 
@@ -145,12 +150,12 @@ impl VisitMut for Visitor<'_> {
                     let nums: Vec<u8> = caps.iter().skip(1).map(|m| {
                         if let Some(m) = m {
                             let m = m.as_str();
-                            let n = if m.ends_with('%') {
-                                (m[..m.len() - 1].parse::<f64>().unwrap() * 2.55).round()
+                            let n = if let Some(m) = m.strip_suffix('%') {
+                                (m.parse::<f64>().unwrap() * 2.55).round()
                             } else {
                                 m.parse::<f64>().unwrap()
                             };
-                            n.max(0.0).min(255.0) as u8
+                            n.clamp(0.0, 255.0) as u8
                         } else {
                             0
                         }
@@ -162,14 +167,12 @@ impl VisitMut for Visitor<'_> {
             }
 
             // convert long hex to short hex
-            if self.params.shorthex {
-                if value.len() == 7 && value.starts_with('#') {
-                    let hex_value = &value[1..];
-                    if let Ok(hex) = u32::from_str_radix(hex_value, 16) {
-                        let compact = get_short_hex(hex);
-                        if hex == get_long_hex(compact) {
-                            value = format!("#{:03x}", get_short_hex(hex));
-                        }
+            if self.params.shorthex && value.len() == 7 && value.starts_with('#') {
+                let hex_value = &value[1..];
+                if let Ok(hex) = u32::from_str_radix(hex_value, 16) {
+                    let compact = get_short_hex(hex);
+                    if hex == get_long_hex(compact) {
+                        value = format!("#{:03x}", get_short_hex(hex));
                     }
                 }
             }
@@ -187,13 +190,29 @@ impl VisitMut for Visitor<'_> {
     }
 
     fn visit_mut_element(&mut self, n: &mut Element) {
+        self.scope.enter(n);
         n.visit_mut_children_with(self);
+        self.scope.leave();
     }
 }
 
-pub fn apply(doc: &mut Document, params: &Params) {
-    let mut v = Visitor::new(params);
-    doc.visit_mut_with(&mut v);
+pub struct ConvertColors;
+
+impl Plugin for ConvertColors {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "convertColors"
+    }
+
+    fn description(&self) -> &str {
+        "converts colors: rgb() to #rrggbb and #rrggbb to #rgb"
+    }
+
+    fn apply(&self, doc: &mut Document, params: &Params, _: &mut Context) {
+        let mut v = Visitor::new(params);
+        doc.visit_mut_with(&mut v);
+    }
 }
 
 #[cfg(test)]
@@ -201,10 +220,9 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::testing::test_plugin;
-    use super::*;
 
     #[testing::fixture("__fixture__/plugins/convertColors*.svg")]
     fn pass(input: PathBuf) {
-        test_plugin(apply, input);
+        test_plugin("convertColors", input);
     }
 }
@@ -5,27 +5,29 @@ use swc_xml_ast::*;
 use swc_xml_visit::{VisitMut, VisitMutWith};
 use serde::Deserialize;
 
-struct Visitor {}
+use crate::namespaces::{split_name, Scope};
+use super::{Context, Plugin};
 
-#[derive(Debug, Deserialize)]
+struct Visitor {
+    scope: Scope,
+}
+
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Params {}
 
-impl Default for Params {
-    fn default() -> Self {
-        Self {}
-    }
-}
-
 impl Visitor {
     fn new() -> Self {
-        Self {}
+        Self {
+            scope: Scope::new(),
+        }
     }
 }
 
 impl VisitMut for Visitor {
     fn visit_mut_element(&mut self, n: &mut Element) {
-        if n.tag_name.to_string() == "ellipse" {
+        self.scope.enter(n);
+        if self.scope.is_svg_element(n, "ellipse") {
             let mut rx = "0".to_string();
             let mut ry = "0".to_string();
             let mut new_attributes = vec![];
@@ -47,7 +49,11 @@ impl VisitMut for Visitor {
                 rx == "auto" ||
                 ry == "auto" // SVG2
             {
-                n.tag_name = "circle".to_string().into();
+                // keep the prefix of `<svg:ellipse>`
+                n.tag_name = match split_name(&n.tag_name).0 {
+                    Some(prefix) => format!("{}:circle", prefix).into(),
+                    None => "circle".into(),
+                };
                 let radius = if rx == "auto" { ry } else  { rx };
                 new_attributes.push(Attribute {
                     span: DUMMY_SP,
@@ -62,12 +68,27 @@ impl VisitMut for Visitor {
             }
         }
         n.visit_mut_children_with(self);
+        self.scope.leave();
     }
 }
 
-pub fn apply(doc: &mut Document, _: &Params) {
-    let mut v = Visitor::new();
-    doc.visit_mut_with(&mut v);
+pub struct ConvertEllipseToCircle;
+
+impl Plugin for ConvertEllipseToCircle {
+    type Params = Params;
+
+    fn name(&self) -> &str {
+        "convertEllipseToCircle"
+    }
+
+    fn description(&self) -> &str {
+        "converts non-eccentric <ellipse>s to <circle>s"
+    }
+
+    fn apply(&self, doc: &mut Document, _: &Params, _: &mut Context) {
+        let mut v = Visitor::new();
+        doc.visit_mut_with(&mut v);
+    }
 }
 
 #[cfg(test)]
@@ -75,10 +96,9 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::testing::test_plugin;
-    use super::*;
 
     #[testing::fixture("__fixture__/plugins/convertEllipseToCircle.*.svg")]
     fn pass(input: PathBuf) {
-        test_plugin(apply, input);
+        test_plugin("convertEllipseToCircle", input);
     }
 }
@@ -1,3 +1,13 @@
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use swc_core::common::{Span, DUMMY_SP};
+use swc_xml_ast::Document;
+
+use crate::config::Config;
+use crate::error::Error;
+
 pub mod cleanup_attrs;
 pub mod cleanup_enable_background;
 pub mod cleanup_ids;
@@ -5,3 +15,310 @@ pub mod cleanup_numeric_values;
 pub mod collapse_groups;
 pub mod convert_colors;
 pub mod convert_ellipse_to_circle;
+pub mod preset_default;
+
+/// Params of a plugin after they were deserialized by [`DynPlugin::parse_params`].
+pub type AnyParams = Box<dyn Any + Send + Sync>;
+
+/// The state of an optimization, given to the plugins.
+#[derive(Debug, Default)]
+pub struct Context {
+    /// Path of the SVG file, from the `path` of the config.
+    pub path: Option<String>,
+    /// Number of the current pass, from 0.
+    pub multipass_count: u32,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A problem reported by a plugin, located by the span of the node which triggered it.
+#[derive(Debug, Clone)]
+pub(crate) struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Context {
+    pub(crate) fn new(path: Option<String>) -> Self {
+        Self {
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// Report a problem which does not stop the optimization.
+    pub fn warn<S: Into<String>>(&mut self, message: S) {
+        self.warn_at(DUMMY_SP, message);
+    }
+
+    /// Report a problem at a node, e.g. `context.warn_at(element.span, ...)`.
+    /// The warning has the line and column of the node unless it was created by a plugin.
+    pub fn warn_at<S: Into<String>>(&mut self, span: Span, message: S) {
+        self.diagnostics.push(Diagnostic {
+            message: message.into(),
+            span,
+        });
+    }
+
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+}
+
+/// A plugin transforming the document.
+///
+/// Plugins shared by threads are registered in a [`Registry`], the others in a [`LocalRegistry`].
+pub trait Plugin {
+    type Params: DeserializeOwned + Default + Send + Sync + 'static;
+
+    /// The SVGO name of the plugin, e.g. `cleanupIds`.
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// The param holding the number of decimals to keep, if the plugin rounds numbers.
+    /// It defaults to [`Config::float_precision`] when the config does not set it.
+    fn precision_param(&self) -> Option<&str> {
+        None
+    }
+
+    fn apply(&self, doc: &mut Document, params: &Self::Params, context: &mut Context);
+}
+
+/// Object safe version of [`Plugin`], implemented for every plugin.
+pub trait DynPlugin {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// See [`Plugin::precision_param`].
+    fn precision_param(&self) -> Option<&str> {
+        None
+    }
+
+    /// Deserialize the params, `None` meaning the default ones.
+    fn parse_params(&self, params: Option<&Value>) -> Result<AnyParams, serde_json::Error>;
+
+    /// Run the plugin with params created by [`DynPlugin::parse_params`].
+    fn apply(&self, doc: &mut Document, params: &AnyParams, context: &mut Context);
+}
+
+impl<P: Plugin> DynPlugin for P {
+    fn name(&self) -> &str {
+        Plugin::name(self)
+    }
+
+    fn description(&self) -> &str {
+        Plugin::description(self)
+    }
+
+    fn precision_param(&self) -> Option<&str> {
+        Plugin::precision_param(self)
+    }
+
+    fn parse_params(&self, params: Option<&Value>) -> Result<AnyParams, serde_json::Error> {
+        let params: P::Params = match params {
+            Some(params) => P::Params::deserialize(params)?,
+            None => Default::default(),
+        };
+        Ok(Box::new(params))
+    }
+
+    fn apply(&self, doc: &mut Document, params: &AnyParams, context: &mut Context) {
+        let params = params
+            .downcast_ref::<P::Params>()
+            .expect("params were not created by this plugin");
+        Plugin::apply(self, doc, params, context)
+    }
+}
+
+/// A plugin which can be shared by threads.
+pub type SharedPlugin = dyn DynPlugin + Send + Sync;
+
+/// Plugins looked up by their SVGO name.
+#[derive(Clone)]
+pub struct Registry {
+    plugins: HashMap<String, Arc<SharedPlugin>>,
+}
+
+impl Registry {
+    /// A registry without any plugin.
+    pub fn empty() -> Self {
+        Self {
+            plugins: HashMap::new(),
+        }
+    }
+
+    /// A registry with all the built-in plugins.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.insert(cleanup_attrs::CleanupAttrs);
+        registry.insert(cleanup_enable_background::CleanupEnableBackground);
+        registry.insert(cleanup_ids::CleanupIds);
+        registry.insert(cleanup_numeric_values::CleanupNumericValues);
+        registry.insert(collapse_groups::CollapseGroups);
+        registry.insert(convert_colors::ConvertColors);
+        registry.insert(convert_ellipse_to_circle::ConvertEllipseToCircle);
+        registry
+    }
+
+    fn insert<P: DynPlugin + Send + Sync + 'static>(&mut self, plugin: P) {
+        self.plugins.insert(plugin.name().to_string(), Arc::new(plugin));
+    }
+
+    /// Add a plugin, failing when its name is already taken, e.g. by a built-in plugin.
+    pub fn register<P: DynPlugin + Send + Sync + 'static>(&mut self, plugin: P) -> Result<(), Error> {
+        check_name(self, plugin.name())?;
+        self.insert(plugin);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<SharedPlugin>> {
+        self.plugins.get(name)
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = &Arc<SharedPlugin>> {
+        self.plugins.values()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The plugins of a [`Registry`] along with plugins which can't leave the current thread,
+/// e.g. the ones calling JavaScript.
+pub struct LocalRegistry<'a> {
+    registry: &'a Registry,
+    plugins: HashMap<String, Box<dyn DynPlugin>>,
+}
+
+impl<'a> LocalRegistry<'a> {
+    pub fn new(registry: &'a Registry) -> Self {
+        Self {
+            registry,
+            plugins: HashMap::new(),
+        }
+    }
+
+    /// Add a plugin, failing when its name is already taken, e.g. by a plugin of the registry.
+    pub fn register<P: DynPlugin + 'static>(&mut self, plugin: P) -> Result<(), Error> {
+        check_name(self, plugin.name())?;
+        self.plugins.insert(plugin.name().to_string(), Box::new(plugin));
+        Ok(())
+    }
+
+    /// Add a plugin configured under `key` instead of its name, so that several plugins may share a name,
+    /// like the custom plugins of SVGO. Its name is still reported in the stats and the warnings.
+    pub fn register_as<P: DynPlugin + 'static>(&mut self, key: String, plugin: P) -> Result<(), Error> {
+        check_name(self, plugin.name())?;
+        check_name(self, &key)?;
+        self.plugins.insert(key, Box::new(plugin));
+        Ok(())
+    }
+}
+
+/// Fail when a plugin would be shadowed by another one or by a preset of the same name.
+fn check_name<R: Lookup>(registry: &R, name: &str) -> Result<(), Error> {
+    if name == preset_default::NAME || registry.lookup(name).is_some() {
+        return Err(Error::DuplicatePlugin(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Where the configured plugins are looked up.
+pub(crate) trait Lookup {
+    type Plugin: ?Sized + DynPlugin;
+
+    fn lookup(&self, name: &str) -> Option<&Self::Plugin>;
+}
+
+impl Lookup for Registry {
+    type Plugin = SharedPlugin;
+
+    fn lookup(&self, name: &str) -> Option<&Self::Plugin> {
+        self.plugins.get(name).map(|plugin| &**plugin)
+    }
+}
+
+impl Lookup for LocalRegistry<'_> {
+    type Plugin = dyn DynPlugin;
+
+    fn lookup(&self, name: &str) -> Option<&Self::Plugin> {
+        match self.plugins.get(name) {
+            Some(plugin) => Some(&**plugin),
+            None => self.registry.lookup(name).map(|plugin| plugin as &Self::Plugin),
+        }
+    }
+}
+
+/// A plugin of the pipeline with its deserialized params.
+pub(crate) struct PreparedPlugin<'a, P: ?Sized = SharedPlugin> {
+    plugin: &'a P,
+    params: AnyParams,
+}
+
+impl<'a, P: ?Sized + DynPlugin> PreparedPlugin<'a, P> {
+    pub fn new<R: Lookup<Plugin = P>>(
+        registry: &'a R,
+        name: &str,
+        params: Option<&Value>,
+        float_precision: Option<i32>,
+    ) -> Result<Self, Error> {
+        let plugin = registry
+            .lookup(name)
+            .ok_or_else(|| Error::UnknownPlugin(name.to_string()))?;
+        let params = match (plugin.precision_param(), float_precision) {
+            (Some(param), Some(precision)) => with_default(params, param, precision.into()),
+            _ => params.cloned(),
+        };
+        let params = plugin.parse_params(params.as_ref()).map_err(|source| Error::InvalidParams {
+            plugin: name.to_string(),
+            source,
+        })?;
+        Ok(Self {
+            plugin,
+            params,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.plugin.name()
+    }
+
+    pub fn apply(&self, doc: &mut Document, context: &mut Context) {
+        self.plugin.apply(doc, &self.params, context)
+    }
+}
+
+/// Set `key` in the params unless they already have it.
+fn with_default(params: Option<&Value>, key: &str, value: Value) -> Option<Value> {
+    match params {
+        None | Some(Value::Null) => Some(serde_json::json!({ key: value })),
+        Some(Value::Object(params)) if !params.contains_key(key) => {
+            let mut params = params.clone();
+            params.insert(key.to_string(), value);
+            Some(Value::Object(params))
+        },
+        Some(params) => Some(params.clone()),
+    }
+}
+
+/// Look up the configured plugins and deserialize their params, expanding presets.
+pub(crate) fn prepare<'a, R: Lookup>(
+    registry: &'a R,
+    config: &Config,
+) -> Result<Vec<PreparedPlugin<'a, R::Plugin>>, Error> {
+    let float_precision = config.float_precision;
+    let mut prepared = vec![];
+    for plugin in config.plugins.iter() {
+        match plugin.name() {
+            preset_default::NAME => {
+                prepared.extend(preset_default::prepare(registry, plugin.params(), float_precision)?)
+            },
+            name => prepared.push(PreparedPlugin::new(registry, name, plugin.params(), float_precision)?),
+        }
+    }
+    Ok(prepared)
+}
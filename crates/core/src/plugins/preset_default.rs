@@ -0,0 +1,180 @@
+// Default preset of SVGO, running the plugins in the SVGO order.
+//
+// @example
+// {
+//   "name": "preset-default",
+//   "params": {
+//     "overrides": {
+//       "cleanupIds": false,
+//       "convertColors": { "currentColor": true }
+//     }
+//   }
+// }
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use super::{Lookup, PreparedPlugin};
+
+pub const NAME: &str = "preset-default";
+
+// Plugins which are not registered yet are skipped.
+const PLUGINS: &[&str] = &[
+    "removeDoctype",
+    "removeXMLProcInst",
+    "removeComments",
+    "removeMetadata",
+    "removeEditorsNSData",
+    "cleanupAttrs",
+    "mergeStyles",
+    "inlineStyles",
+    "minifyStyles",
+    "cleanupIds",
+    "removeUselessDefs",
+    "cleanupNumericValues",
+    "convertColors",
+    "removeUnknownsAndDefaults",
+    "removeNonInheritableGroupAttrs",
+    "removeUselessStrokeAndFill",
+    "removeViewBox",
+    "cleanupEnableBackground",
+    "removeHiddenElems",
+    "removeEmptyText",
+    "convertShapeToPath",
+    "convertEllipseToCircle",
+    "moveElemsAttrsToGroup",
+    "moveGroupAttrsToElems",
+    "collapseGroups",
+    "convertPathData",
+    "convertTransform",
+    "removeEmptyAttrs",
+    "removeEmptyContainers",
+    "mergePaths",
+    "removeUnusedNS",
+    "sortAttrs",
+    "sortDefsChildren",
+    "removeTitle",
+    "removeDesc",
+];
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Override {
+    /// `false` disables the plugin.
+    Enabled(bool),
+    /// Params replacing the default ones.
+    Params(Value),
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Params {
+    #[serde(default)]
+    pub overrides: HashMap<String, Override>,
+}
+
+pub(crate) fn prepare<'a, R: Lookup>(
+    registry: &'a R,
+    params: Option<&Value>,
+    float_precision: Option<i32>,
+) -> Result<Vec<PreparedPlugin<'a, R::Plugin>>, Error> {
+    let Params { overrides } = match params {
+        Some(params) => Params::deserialize(params).map_err(|source| Error::InvalidParams {
+            plugin: NAME.to_string(),
+            source,
+        })?,
+        None => Default::default(),
+    };
+
+    if let Some(name) = overrides.keys().find(|name| !PLUGINS.contains(&name.as_str())) {
+        return Err(Error::UnknownOverride {
+            preset: NAME.to_string(),
+            plugin: name.to_string(),
+        });
+    }
+
+    let mut plugins = vec![];
+    for name in PLUGINS {
+        if registry.lookup(name).is_none() {
+            continue;
+        }
+        match overrides.get(*name) {
+            Some(Override::Enabled(false)) => {},
+            Some(Override::Params(params)) => plugins.push(PreparedPlugin::new(registry, name, Some(params), float_precision)?),
+            _ => plugins.push(PreparedPlugin::new(registry, name, None, float_precision)?),
+        }
+    }
+    Ok(plugins)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    use pretty_assertions::assert_eq;
+
+    use crate::plugins::Registry;
+    use super::*;
+
+    fn names(params: Option<Value>) -> Result<Vec<String>, Error> {
+        let registry = Registry::new();
+        let plugins = prepare(&registry, params.as_ref(), None)?;
+        Ok(plugins.iter().map(|prepared| prepared.name().to_string()).collect())
+    }
+
+    #[test]
+    fn default_order() {
+        assert_eq!(names(None).unwrap(), vec![
+            "cleanupAttrs",
+            "cleanupIds",
+            "cleanupNumericValues",
+            "convertColors",
+            "cleanupEnableBackground",
+            "convertEllipseToCircle",
+            "collapseGroups",
+        ]);
+    }
+
+    #[test]
+    fn overrides() {
+        let params = serde_json::json!({
+            "overrides": {
+                "cleanupIds": false,
+                "collapseGroups": true,
+                "convertColors": { "currentColor": true },
+                "removeViewBox": false,
+            }
+        });
+        assert_eq!(names(Some(params)).unwrap(), vec![
+            "cleanupAttrs",
+            "cleanupNumericValues",
+            "convertColors",
+            "cleanupEnableBackground",
+            "convertEllipseToCircle",
+            "collapseGroups",
+        ]);
+    }
+
+    #[test]
+    fn unknown_override() {
+        let params = serde_json::json!({
+            "overrides": {
+                "cleanupIDs": false,
+            }
+        });
+        let result = names(Some(params));
+        assert!(matches!(result, Err(Error::UnknownOverride { plugin, .. }) if plugin == "cleanupIDs"));
+    }
+
+    #[test]
+    fn invalid_override_params() {
+        let params = serde_json::json!({
+            "overrides": {
+                "cleanupIds": { "minify": "yes" },
+            }
+        });
+        let result = names(Some(params));
+        assert!(matches!(result, Err(Error::InvalidParams { plugin, .. }) if plugin == "cleanupIds"));
+    }
+}
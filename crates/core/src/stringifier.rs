@@ -1,14 +1,84 @@
+use std::fmt::{self, Write};
+use std::io;
+
 use swc_xml_ast::*;
 use regex::Regex;
+use serde::Deserialize;
 
 use crate::collections::get_text_elems;
+use crate::parser::{internal_subset, xml_space};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Eol {
+    #[default]
     Lf,
     Crlf
 }
 
+/// The subset of [`StringifyOptions`] configurable like SVGO's `js2svg`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Js2SvgOptions {
+    #[serde(default = "default_indent")]
+    pub indent: usize,
+    #[serde(default)]
+    pub pretty: bool,
+    #[serde(default = "default_use_short_tags")]
+    pub use_short_tags: bool,
+    #[serde(default)]
+    pub eol: Eol,
+    #[serde(default)]
+    pub final_newline: bool,
+}
+
+fn default_indent() -> usize {
+    4
+}
+
+fn default_use_short_tags() -> bool {
+    true
+}
+
+impl Default for Js2SvgOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            pretty: false,
+            use_short_tags: true,
+            eol: Eol::Lf,
+            final_newline: false,
+        }
+    }
+}
+
+impl From<&Js2SvgOptions> for StringifyOptions {
+    fn from(options: &Js2SvgOptions) -> Self {
+        Self {
+            indent: options.indent,
+            pretty: options.pretty,
+            use_short_tags: options.use_short_tags,
+            eol: options.eol.clone(),
+            final_newline: options.final_newline,
+            ..Default::default()
+        }
+    }
+}
+
+const ENTITIES: &str = r#"[&'"<>]"#;
+const VAL_ENTITIES: &str = r#"[&"<>]"#;
+
+fn xml_entity(c: char) -> Option<&'static str> {
+    match c {
+        '&' => Some("&amp;"),
+        '\'' => Some("&apos;"),
+        '"' => Some("&quot;"),
+        '>' => Some("&gt;"),
+        '<' => Some("&lt;"),
+        _ => None,
+    }
+}
+
 pub struct StringifyOptions {
     pub doctype_start: String,
     pub doctype_end: String,
@@ -29,8 +99,11 @@ pub struct StringifyOptions {
     pub text_start: String,
     pub text_end: String,
     pub indent: usize,
+    /// Characters of the texts to encode.
     pub reg_entities: Regex,
+    /// Characters of the attribute values to encode.
     pub reg_val_entities: Regex,
+    /// Encodes the characters matched by `reg_entities` and `reg_val_entities`, into XML entities when none.
     pub encode_entity: Option<Box<dyn Fn(char) -> String>>,
     pub pretty: bool,
     pub use_short_tags: bool,
@@ -60,20 +133,9 @@ impl Default for StringifyOptions {
             text_start: "".to_string(),
             text_end: "".to_string(),
             indent: 4,
-            reg_entities: Regex::new(r#"[&'"<>]"#).unwrap(),
-            reg_val_entities: Regex::new(r#"[&"<>]"#).unwrap(),
-            encode_entity: Some(Box::new(
-                |c: char| -> String {
-                    match c {
-                        '&' => "&amp;".to_string(),
-                        '\'' => "&apos;".to_string(),
-                        '"' => "&quot;".to_string(),
-                        '>' => "&gt;".to_string(),
-                        '<' => "&lt;".to_string(),
-                        _ => c.to_string(),
-                    }
-                }
-            )),
+            reg_entities: Regex::new(ENTITIES).unwrap(),
+            reg_val_entities: Regex::new(VAL_ENTITIES).unwrap(),
+            encode_entity: None,
             pretty: false,
             use_short_tags: true,
             eol: Eol::Lf,
@@ -82,21 +144,79 @@ impl Default for StringifyOptions {
     }
 }
 
-#[derive(Debug, Default, Clone)]
-struct Ctx<'a> {
-    indent: String,
+// saved and restored around every element, so kept cheap to copy
+#[derive(Debug, Default, Clone, Copy)]
+struct Ctx {
     indent_level: usize,
-    text_context: Option<&'a Element>,
+    // whether the current node is in a text element
+    text_element: bool,
+    // whether the current node is in a `xml:space="preserve"` subtree
+    xml_space_preserve: bool,
+}
+
+impl Ctx {
+    /// Whitespace is significant in text elements and `xml:space="preserve"` subtrees,
+    /// no indentation or line break is added there.
+    fn text_context(&self) -> bool {
+        self.text_element || self.xml_space_preserve
+    }
 }
 
-pub struct Stringifier<'a> {
+pub struct Stringifier {
     options: StringifyOptions,
     defaults: StringifyOptions,
-    ctx: Ctx<'a>,
+    indent: String,
+    ctx: Ctx,
     text_elems: Vec<&'static str>,
+    // whether `reg_entities` and `reg_val_entities` are the default ones, scanned for without the regex
+    default_entities: bool,
+    default_val_entities: bool,
+}
+
+/// A sink remembering whether the output ends with a line break, for `final_newline`.
+struct Sink<'a, W: Write + ?Sized> {
+    out: &'a mut W,
+    empty: bool,
+    ends_with_newline: bool,
+}
+
+impl<W: Write + ?Sized> Write for Sink<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(last) = s.as_bytes().last() {
+            self.empty = false;
+            self.ends_with_newline = *last == b'\n';
+        }
+        self.out.write_str(s)
+    }
+}
+
+/// Counts the bytes of the output instead of keeping it.
+#[derive(Debug, Default)]
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
 }
 
-impl Stringifier<'_> {
+/// Adapts an `io::Write` to `fmt::Write`, keeping the I/O error which `fmt::Error` cannot carry.
+struct IoWriter<W: io::Write> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+impl Stringifier {
     pub fn new(mut user_options: StringifyOptions) -> Self {
         let eol = match user_options.eol {
             Eol::Crlf => "\r\n",
@@ -104,249 +224,405 @@ impl Stringifier<'_> {
         };
 
         if user_options.pretty {
-            user_options.doctype_end = user_options.doctype_end + eol;
-            user_options.proc_inst_end = user_options.proc_inst_end + eol;
-            user_options.comment_end = user_options.comment_end + eol;
-            user_options.cdata_end = user_options.cdata_end + eol;
-            user_options.tag_short_end = user_options.tag_short_end + eol;
-            user_options.tag_open_end = user_options.tag_open_end + eol;
-            user_options.tag_close_end = user_options.tag_close_end + eol;
-            user_options.text_end = user_options.text_end + eol;
+            user_options.doctype_end += eol;
+            user_options.proc_inst_end += eol;
+            user_options.comment_end += eol;
+            user_options.cdata_end += eol;
+            user_options.tag_short_end += eol;
+            user_options.tag_open_end += eol;
+            user_options.tag_close_end += eol;
+            user_options.text_end += eol;
         }
 
         let indent = " ".repeat(user_options.indent);
-        let ctx = Ctx {
-            indent,
-            indent_level: 0,
-            text_context: None,
-        };
 
         Self {
+            default_entities: user_options.reg_entities.as_str() == ENTITIES,
+            default_val_entities: user_options.reg_val_entities.as_str() == VAL_ENTITIES,
             options: user_options,
             defaults: Default::default(),
-            ctx,
+            indent,
+            ctx: Default::default(),
             text_elems: get_text_elems(),
         }
     }
 
-    fn emit_document(&mut self, n: &Document) -> String {
+    /// Write the document into `out` in a single pass.
+    pub fn write<W: Write + ?Sized>(&mut self, doc: &Document, out: &mut W) -> fmt::Result {
+        let mut sink = Sink {
+            out,
+            empty: true,
+            ends_with_newline: false,
+        };
+        self.emit_document(doc, &mut sink)
+    }
+
+    pub fn stringify(&mut self, doc: &Document) -> String {
         let mut svg = String::new();
+        // writing into a `String` cannot fail
+        self.write(doc, &mut svg).unwrap();
+        svg
+    }
 
+    /// The size of the output in bytes, without building it.
+    pub fn size(&mut self, doc: &Document) -> usize {
+        let mut counter = Counter::default();
+        self.write(doc, &mut counter).unwrap();
+        counter.0
+    }
+
+    fn emit_document<W: Write + ?Sized>(&mut self, n: &Document, out: &mut Sink<W>) -> fmt::Result {
         for n in n.children.iter() {
-            svg += &self.emit_child(n);
+            self.emit_child(n, out)?;
         }
 
-        if self.options.final_newline && svg.len() > 0 && svg[svg.len() - 1..].to_string() != "\n" {
+        if self.options.final_newline && !out.empty && !out.ends_with_newline {
             let eol = match self.options.eol {
                 Eol::Crlf => "\r\n",
                 Eol::Lf => "\n",
             };
-            svg += eol;
+            out.write_str(eol)?;
         }
 
-        svg
+        Ok(())
     }
 
-    fn emit_child(&mut self, n: &Child) -> String {
+    fn emit_child<W: Write + ?Sized>(&mut self, n: &Child, out: &mut W) -> fmt::Result {
         self.ctx.indent_level += 1;
         let result = match n {
-            Child::DocumentType(n) => self.emit_document_doctype(n),
-            Child::Element(n) => self.emit_element(n),
-            Child::Text(n) => self.emit_text(n),
-            Child::Comment(n) => self.emit_comment(n),
-            Child::ProcessingInstruction(n) => self.emit_processing_instruction(n),
-            Child::CdataSection(n) => self.emit_cdata_section(n),
+            Child::DocumentType(n) => self.emit_document_doctype(n, out),
+            Child::Element(n) => self.emit_element(n, out),
+            Child::Text(n) => self.emit_text(n, out),
+            Child::Comment(n) => self.emit_comment(n, out),
+            Child::ProcessingInstruction(n) => self.emit_processing_instruction(n, out),
+            Child::CdataSection(n) => self.emit_cdata_section(n, out),
         };
         self.ctx.indent_level -= 1;
         result
     }
 
-    fn emit_document_doctype(&mut self, n: &DocumentType) -> String {
-        let mut doctype = String::from(self.options.doctype_start.clone());
-        if let Some(name) = &n.name {
-            doctype.push(' ');
-            doctype.push_str(name);
-        }
-
-        doctype.push_str(&self.options.doctype_end);
-
-        doctype
+    fn emit_document_doctype<W: Write + ?Sized>(&mut self, n: &DocumentType, out: &mut W) -> fmt::Result {
+        out.write_str(&self.options.doctype_start)?;
+        write_doctype_declaration(n, out)?;
+        out.write_str(&self.options.doctype_end)
     }
 
-    fn emit_element(&mut self, n: &Element) -> String {
-        let mut element = String::new();
+    fn emit_element<W: Write + ?Sized>(&mut self, n: &Element, out: &mut W) -> fmt::Result {
+        let parent_ctx = self.ctx;
+        let text_context = parent_ctx.text_context();
 
         // empty element and short tag
-        if n.children.len() == 0 {
+        if n.children.is_empty() {
+            self.write_indent(text_context, out)?;
             if self.options.use_short_tags {
-                element.push_str(&self.create_indent());
-                element.push_str(&self.options.tag_short_start);
-                element.push_str(&n.tag_name);
-                element.push_str(&self.emit_attributes(n));
-                element.push_str(&self.options.tag_short_end);
+                out.write_str(&self.options.tag_short_start)?;
+                out.write_str(&n.tag_name)?;
+                self.emit_attributes(n, out)?;
+                out.write_str(&self.end_options().tag_short_end)?;
             } else {
-                element.push_str(&self.create_indent());
-                element.push_str(&self.options.tag_open_start);
-                element.push_str(&n.tag_name);
-                element.push_str(&self.emit_attributes(n));
-                element.push_str(&self.options.tag_open_end);
-                element.push_str(&self.options.tag_close_start);
-                element.push_str(&n.tag_name);
-                element.push_str(&self.options.tag_close_end);
+                out.write_str(&self.options.tag_open_start)?;
+                out.write_str(&n.tag_name)?;
+                self.emit_attributes(n, out)?;
+                out.write_str(&self.options.tag_open_end)?;
+                out.write_str(&self.options.tag_close_start)?;
+                out.write_str(&n.tag_name)?;
+                out.write_str(&self.end_options().tag_close_end)?;
             }
-            return element;
+            return Ok(());
         }
 
-        let mut tag_open_start = self.options.tag_open_start.clone();
-        let mut tag_open_end = self.options.tag_open_end.clone();
-        let mut tag_close_start = self.options.tag_close_start.clone();
-        let mut tag_close_end = self.options.tag_close_end.clone();
-        let mut open_indent = self.create_indent();
-        let mut close_indent = self.create_indent();
-        if self.ctx.text_context.is_some() {
-            tag_open_start = self.defaults.tag_open_start.clone();
-            tag_open_end = self.defaults.tag_open_end.clone();
-            tag_close_start = self.defaults.tag_close_start.clone();
-            tag_close_end = self.defaults.tag_close_end.clone();
-            open_indent = "".to_string();
-        } else if self.text_elems.contains(&n.tag_name.to_string().as_str()) {
-            tag_open_end = self.defaults.tag_open_end.clone();
-            tag_close_start = self.defaults.tag_close_start.clone();
-            close_indent = "".to_string();
-
-            unsafe {
-                let r = n as *const Element;
-                self.ctx.text_context = r.as_ref();
-            }
+        if self.text_elems.contains(&&*n.tag_name) {
+            self.ctx.text_element = true;
         }
-
-        let mut children = String::new();
-        for child in &n.children {
-            children.push_str(&self.emit_child(child))
+        if let Some(preserve) = xml_space(n) {
+            self.ctx.xml_space_preserve = preserve;
         }
+        let children_text_context = self.ctx.text_context();
 
-        if let Some(text_context) = self.ctx.text_context {
-            if text_context == n {
-                self.ctx.text_context = None;
-            }
-        }
+        // the start tag is in the content of the parent, the end of the tags in the content of the element
+        self.write_indent(text_context, out)?;
+        out.write_str(&self.options_at(text_context).tag_open_start)?;
+        out.write_str(&n.tag_name)?;
+        self.emit_attributes(n, out)?;
+        out.write_str(&self.options_at(children_text_context).tag_open_end)?;
 
-        element.push_str(&open_indent);
-        element.push_str(&tag_open_start);
-        element.push_str(&n.tag_name);
-        element.push_str(&self.emit_attributes(n));
-        element.push_str(&tag_open_end);
-        element.push_str(&children);
-        element.push_str(&close_indent);
-        element.push_str(&tag_close_start);
-        element.push_str(&n.tag_name);
-        element.push_str(&tag_close_end);
+        for child in &n.children {
+            self.emit_child(child, out)?;
+        }
 
-        element
+        self.write_indent(children_text_context, out)?;
+        out.write_str(&self.options_at(children_text_context).tag_close_start)?;
+        out.write_str(&n.tag_name)?;
+        self.ctx = parent_ctx;
+        out.write_str(&self.options_at(text_context).tag_close_end)
     }
 
-    fn emit_attributes(&mut self, n: &Element) -> String {
-        let mut attrs = String::new();
-
+    fn emit_attributes<W: Write + ?Sized>(&mut self, n: &Element, out: &mut W) -> fmt::Result {
         for attr in &n.attributes {
-            if let Some(value) = &attr.value {
-                let encoded_value = if let Some(encode_entity) = &self.options.encode_entity {
-                    value.chars().map(|c| {
-                        let s = c.to_string();
-                        if Regex::is_match(&self.options.reg_val_entities, &s) {
-                            encode_entity(c)
-                        } else {
-                            s
-                        }
-                    }).collect()
-                } else {
-                    value.to_string()
-                };
-                attrs.push_str(" ");
-                attrs.push_str(&attr.name.to_string());
-                attrs.push_str(&self.options.attr_start);
-                attrs.push_str(&encoded_value);
-                attrs.push_str(&self.options.attr_end);
+            out.write_char(' ')?;
+            out.write_str(&attr.name)?;
+            let Some(value) = &attr.value else {
+                continue;
+            };
+            if !self.default_val_entities {
+                out.write_str(&self.options.attr_start)?;
+                self.write_matched(value, &self.options.reg_val_entities, out)?;
+                out.write_str(&self.options.attr_end)?;
+            } else if self.prefers_single_quotes(value) {
+                out.write_str("='")?;
+                self.write_escaped(value, |byte| matches!(byte, b'&' | b'\'' | b'<' | b'>'), out)?;
+                out.write_char('\'')?;
             } else {
-                attrs.push_str(" ");
-                attrs.push_str(&attr.name.to_string());
+                out.write_str(&self.options.attr_start)?;
+                self.write_escaped(value, |byte| matches!(byte, b'&' | b'"' | b'<' | b'>'), out)?;
+                out.write_str(&self.options.attr_end)?;
             }
         }
-
-        attrs
+        Ok(())
     }
 
-    fn emit_text(&mut self, n: &Text) -> String {
-        let mut text = String::new();
-
-        text.push_str(&self.create_indent());
-        text.push_str(&self.options.text_start);
-
-        let encoded_data = if let Some(encode_entity) = &self.options.encode_entity {
-            n.data.chars().map(|c| {
-                let s = c.to_string();
-                if Regex::is_match(&self.options.reg_entities, &s) {
-                    encode_entity(c)
-                } else {
-                    s
-                }
-            }).collect()
+    fn emit_text<W: Write + ?Sized>(&mut self, n: &Text, out: &mut W) -> fmt::Result {
+        self.write_indent(self.ctx.text_context(), out)?;
+        out.write_str(&self.options.text_start)?;
+        if self.default_entities {
+            self.write_escaped(&n.data, |byte| matches!(byte, b'&' | b'\'' | b'"' | b'<' | b'>'), out)?;
         } else {
-            n.data.to_string()
-        };
-        text.push_str(&encoded_data);
+            self.write_matched(&n.data, &self.options.reg_entities, out)?;
+        }
 
-        if self.ctx.text_context.is_none() {
-            text.push_str(&self.options.text_end);
+        if !self.ctx.text_context() {
+            out.write_str(&self.options.text_end)?;
         }
+        Ok(())
+    }
 
-        text
+    fn emit_comment<W: Write + ?Sized>(&mut self, n: &Comment, out: &mut W) -> fmt::Result {
+        out.write_str(&self.options.comment_start)?;
+        out.write_str(&n.data)?;
+        out.write_str(&self.end_options().comment_end)
     }
 
-    fn emit_comment(&mut self, n: &Comment) -> String {
-        let mut comment = String::new();
+    fn emit_processing_instruction<W: Write + ?Sized>(
+        &mut self,
+        n: &ProcessingInstruction,
+        out: &mut W,
+    ) -> fmt::Result {
+        out.write_str(&self.options.proc_inst_start)?;
+        out.write_str(&n.target)?;
+        if !n.data.is_empty() {
+            out.write_char(' ')?;
+            out.write_str(&n.data)?;
+        }
+        out.write_str(&self.end_options().proc_inst_end)
+    }
 
-        comment.push_str(&self.options.comment_start);
-        comment.push_str(&n.data);
-        comment.push_str(&self.options.comment_end);
+    fn emit_cdata_section<W: Write + ?Sized>(&mut self, n: &CdataSection, out: &mut W) -> fmt::Result {
+        self.write_indent(self.ctx.text_context(), out)?;
+        out.write_str(&self.options.cdata_start)?;
+        out.write_str(&n.data)?;
+        out.write_str(&self.end_options().cdata_end)
+    }
 
-        comment
+    /// Whether quoting the value with `'` needs less escaping than with `"`, unless the quotes are configured.
+    ///
+    /// A custom `encode_entity` may not escape `'`, so the value is then always quoted with `"`.
+    fn prefers_single_quotes(&self, value: &str) -> bool {
+        if self.options.encode_entity.is_some()
+            || self.options.attr_start != self.defaults.attr_start
+            || self.options.attr_end != self.defaults.attr_end
+        {
+            return false;
+        }
+        let (mut double, mut single) = (0, 0);
+        for byte in value.bytes() {
+            match byte {
+                b'"' => double += 1,
+                b'\'' => single += 1,
+                _ => {},
+            }
+        }
+        double > single
     }
 
-    fn emit_processing_instruction(&mut self, n: &ProcessingInstruction) -> String {
-        let mut processing_instruction = String::new();
+    /// Write the special ASCII characters with `encode_entity`, the others as is.
+    fn write_escaped<W: Write + ?Sized>(&self, data: &str, is_special: fn(u8) -> bool, out: &mut W) -> fmt::Result {
+        let mut start = 0;
+        for (index, byte) in data.bytes().enumerate() {
+            if is_special(byte) {
+                out.write_str(&data[start..index])?;
+                self.write_entity(byte as char, out)?;
+                start = index + 1;
+            }
+        }
+        out.write_str(&data[start..])
+    }
 
-        processing_instruction.push_str(&self.options.proc_inst_start);
-        processing_instruction.push_str(&n.target);
-        processing_instruction.push(' ');
-        processing_instruction.push_str(&n.data);
-        processing_instruction.push_str(&self.options.proc_inst_end);
+    /// Write the characters matching `entities` with `encode_entity`, the others as is.
+    fn write_matched<W: Write + ?Sized>(&self, data: &str, entities: &Regex, out: &mut W) -> fmt::Result {
+        let mut start = 0;
+        for m in entities.find_iter(data) {
+            out.write_str(&data[start..m.start()])?;
+            for c in m.as_str().chars() {
+                self.write_entity(c, out)?;
+            }
+            start = m.end();
+        }
+        out.write_str(&data[start..])
+    }
 
-        processing_instruction
+    fn write_entity<W: Write + ?Sized>(&self, c: char, out: &mut W) -> fmt::Result {
+        match &self.options.encode_entity {
+            Some(encode_entity) => out.write_str(&encode_entity(c)),
+            None => match xml_entity(c) {
+                Some(entity) => out.write_str(entity),
+                None => out.write_char(c),
+            },
+        }
     }
 
-    fn emit_cdata_section(&mut self, n: &CdataSection) -> String {
-        let mut cdata_section = String::new();
+    fn write_indent<W: Write + ?Sized>(&self, text_context: bool, out: &mut W) -> fmt::Result {
+        if self.options.pretty && !text_context {
+            for _ in 1..self.ctx.indent_level {
+                out.write_str(&self.indent)?;
+            }
+        }
+        Ok(())
+    }
 
-        cdata_section.push_str(&self.create_indent());
-        cdata_section.push_str(&self.options.cdata_start);
-        cdata_section.push_str(&n.data);
-        cdata_section.push_str(&self.options.cdata_end);
+    fn options_at(&self, text_context: bool) -> &StringifyOptions {
+        if text_context {
+            &self.defaults
+        } else {
+            &self.options
+        }
+    }
 
-        cdata_section
+    /// The options ending the nodes, without the line breaks of pretty printing in text context.
+    fn end_options(&self) -> &StringifyOptions {
+        self.options_at(self.ctx.text_context())
     }
+}
 
-    fn create_indent(&mut self) -> String {
-        let mut indent = String::new();
-        if self.options.pretty && self.ctx.text_context.is_none() {
-            indent = self.ctx.indent.repeat(self.ctx.indent_level - 1);
-        }
-        indent
+fn write_doctype_declaration<W: Write + ?Sized>(n: &DocumentType, out: &mut W) -> fmt::Result {
+    fn write_id<W: Write + ?Sized>(id: &str, out: &mut W) -> fmt::Result {
+        let quote = if id.contains('"') { '\'' } else { '"' };
+        out.write_char(' ')?;
+        out.write_char(quote)?;
+        out.write_str(id)?;
+        out.write_char(quote)
     }
+
+    if let Some(name) = &n.name {
+        out.write_char(' ')?;
+        out.write_str(name)?;
+    }
+    match (&n.public_id, &n.system_id) {
+        (Some(public_id), system_id) => {
+            out.write_str(" PUBLIC")?;
+            write_id(public_id, out)?;
+            if let Some(system_id) = system_id {
+                write_id(system_id, out)?;
+            }
+        },
+        (None, Some(system_id)) => {
+            out.write_str(" SYSTEM")?;
+            write_id(system_id, out)?;
+        },
+        (None, None) => {},
+    }
+    if let Some(subset) = internal_subset(n) {
+        out.write_str(" [")?;
+        out.write_str(subset)?;
+        out.write_char(']')?;
+    }
+    Ok(())
+}
+
+/// The declaration of a doctype after `<!DOCTYPE`, like `data.doctype` of the XAST.
+pub fn doctype_declaration(n: &DocumentType) -> String {
+    let mut declaration = String::new();
+    let _ = write_doctype_declaration(n, &mut declaration);
+    declaration
 }
 
 /// convert XAST to SVG string
 pub fn stringify_svg(doc: &Document, user_options: StringifyOptions) -> String {
-    let mut stringifier = Stringifier::new(user_options);
-    stringifier.emit_document(doc)
+    Stringifier::new(user_options).stringify(doc)
+}
+
+/// Write the SVG into an `io::Write`, e.g. a buffered file, without building it in memory.
+pub fn write_svg<W: io::Write>(doc: &Document, user_options: StringifyOptions, out: W) -> io::Result<()> {
+    let mut writer = IoWriter {
+        inner: out,
+        error: None,
+    };
+    Stringifier::new(user_options)
+        .write(doc, &mut writer)
+        .map_err(|_| writer.error.take().unwrap_or_else(|| io::Error::other(fmt::Error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::parser::parse_svg;
+    use crate::testing::test_stringify;
+    use super::*;
+
+    #[testing::fixture("__fixture__/stringifier/*.svg")]
+    fn pass(input: PathBuf) {
+        test_stringify(input);
+    }
+
+    #[test]
+    fn sinks() {
+        let input = "<svg>\n  <text>a &amp; b</text>\n  <g/>\n</svg>";
+        let doc = parse_svg(input.to_string(), None, &Default::default()).unwrap().doc;
+        let options = || StringifyOptions {
+            pretty: true,
+            final_newline: true,
+            ..Default::default()
+        };
+        let svg = stringify_svg(&doc, options());
+        assert_eq!(svg, "<svg>\n    <text>a &amp; b</text>\n    <g/>\n</svg>\n");
+
+        let mut bytes = vec![];
+        write_svg(&doc, options(), &mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), svg);
+        assert_eq!(Stringifier::new(options()).size(&doc), svg.len());
+    }
+
+    #[test]
+    fn entities() {
+        let input = r#"<svg a="x &amp; &lt;y&gt;" b='"q" &apos;' c="&quot;&apos;"><text>'a' &amp; "b" &lt;</text></svg>"#;
+        let doc = parse_svg(input.to_string(), None, &Default::default()).unwrap().doc;
+        assert_eq!(
+            stringify_svg(&doc, StringifyOptions::default()),
+            r#"<svg a="x &amp; &lt;y&gt;" b='"q" &apos;' c="&quot;'"><text>&apos;a&apos; &amp; &quot;b&quot; &lt;</text></svg>"#,
+        );
+
+        // the overrides go through the regexes, and keep the configured quotes
+        let options = StringifyOptions {
+            reg_entities: Regex::new("[<a]").unwrap(),
+            reg_val_entities: Regex::new("[\"x]").unwrap(),
+            encode_entity: Some(Box::new(|c| format!("&#{};", c as u32))),
+            ..Default::default()
+        };
+        assert_eq!(
+            stringify_svg(&doc, options),
+            r#"<svg a="&#120; & <y>" b="&#34;q&#34; '" c="&#34;'"><text>'&#97;' & "b" &#60;</text></svg>"#,
+        );
+
+        // a custom encoding leaving `'` as is keeps the `"` quotes
+        let input = r#"<svg a="say &quot;hi&quot; it's"/>"#;
+        let doc = parse_svg(input.to_string(), None, &Default::default()).unwrap().doc;
+        let options = StringifyOptions {
+            encode_entity: Some(Box::new(|c| match c {
+                '&' | '"' | '<' | '>' => format!("&#{};", c as u32),
+                c => c.to_string(),
+            })),
+            ..Default::default()
+        };
+        assert_eq!(stringify_svg(&doc, options), r#"<svg a="say &#34;hi&#34; it's"/>"#);
+    }
 }
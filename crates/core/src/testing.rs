@@ -1,41 +1,54 @@
 use std::{path::PathBuf, fs};
 
 use regex::Regex;
-use serde::de::DeserializeOwned;
-use swc_xml_ast::Document;
+use serde_json::Value;
 
 use crate::parser::parse_svg;
+use crate::plugins::{Context, Registry};
 use crate::stringifier::{stringify_svg, StringifyOptions};
 
 #[cfg(test)]
 use pretty_assertions::assert_eq;
 
-pub fn test_plugin<F, P>(
-    apply: F,
-    input: PathBuf,
-) where
-    F: FnOnce(&mut Document, &P),
-    P: DeserializeOwned + Default
-{
+fn read_fixture(input: PathBuf) -> Vec<String> {
     let text = fs::read_to_string(input).unwrap();
     let re = Regex::new(r"\s*@@@\s*").unwrap();
-    let fields: Vec<&str> = re.split(&text).collect();
+    re.split(&text).map(str::to_string).collect()
+}
+
+pub fn test_plugin(name: &str, input: PathBuf) {
+    let fields = read_fixture(input);
 
     let input = fields[0].trim();
     let expected = fields[1].trim();
-    let params: P = if fields.len() > 2 {
+    let params: Option<Value> = if fields.len() > 2 {
         let json_str = fields[2].trim();
-        serde_json::from_str(&json_str).unwrap()
+        Some(serde_json::from_str(json_str).unwrap())
     } else {
-        Default::default()
+        None
     };
 
-    let mut doc = parse_svg(input.to_string()).unwrap();
+    let registry = Registry::new();
+    let plugin = registry.get(name).unwrap();
+    let params = plugin.parse_params(params.as_ref()).unwrap();
+
+    let mut doc = parse_svg(input.to_string(), None, &Default::default()).unwrap().doc;
 
-    apply(&mut doc, &params);
+    plugin.apply(&mut doc, &params, &mut Context::default());
     let result = stringify_svg(&doc, StringifyOptions {
         pretty: true,
         ..Default::default()
     });
     assert_eq!(result.trim_end(), expected);
-}
\ No newline at end of file
+}
+
+/// Check that a fixture is printed back as expected after parsing, pretty printed.
+pub fn test_stringify(input: PathBuf) {
+    let fields = read_fixture(input);
+    let doc = parse_svg(fields[0].trim().to_string(), None, &Default::default()).unwrap().doc;
+    let result = stringify_svg(&doc, StringifyOptions {
+        pretty: true,
+        ..Default::default()
+    });
+    assert_eq!(result.trim_end(), fields[1].trim());
+}
@@ -0,0 +1,160 @@
+#![deny(clippy::all)]
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use svgo_rs::{optimize as optimize_core, Config};
+use wasm_bindgen::prelude::*;
+
+// the types of the Node binding without its custom plugins, checked against `index.d.ts` by the tests
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+const TYPES: &str = r#"
+/** A problem which did not stop the optimization. */
+export interface Warning {
+  message: string
+  plugin?: string
+  path?: string
+  line?: number
+  column?: number
+  snippet?: string
+}
+/** What a plugin did, summed over all the passes. */
+export interface PluginStats {
+  name: string
+  time: number
+  savedBytes?: number
+}
+export interface Output {
+  data: string
+  warnings: Array<Warning>
+  passes: number
+  originalSize: number
+  optimizedSize: number
+  plugins: Array<PluginStats>
+}
+/** How the parser treats whitespace. */
+export interface ParseOptions {
+  preserveXmlSpace?: boolean
+  preserveRawText?: boolean
+  verbatimComments?: boolean
+}
+/** Options of the output. */
+export interface Js2SvgOptions {
+  indent?: number
+  pretty?: boolean
+  useShortTags?: boolean
+  eol?: 'lf' | 'crlf'
+  finalNewline?: boolean
+}
+/** A plugin of the config with its params, e.g. `{ name: 'preset-default', params: { overrides: {} } }`. */
+export interface PluginItem {
+  name: string
+  params?: Record<string, any>
+}
+/** The SVGO config. */
+export interface Config {
+  path?: string
+  multipass?: boolean
+  stats?: boolean
+  floatPrecision?: number
+  parser?: ParseOptions
+  js2svg?: Js2SvgOptions
+  datauri?: 'base64' | 'enc' | 'unenc'
+  plugins?: Array<string | PluginItem>
+}
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = TYPES;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Config")]
+    pub type JsConfig;
+
+    #[wasm_bindgen(typescript_type = "Output")]
+    pub type JsOutput;
+}
+
+/// The core of SVGO RS
+#[wasm_bindgen]
+pub fn optimize(input: String, config: Option<JsConfig>) -> Result<JsOutput, JsError> {
+    let config = match config {
+        Some(config) => config_from_value(serde_wasm_bindgen::from_value(config.into())?)?,
+        None => Config::default(),
+    };
+    let output = optimize_core(input, &config).map_err(|err| JsError::new(&err.to_string()))?;
+    let output = output.serialize(&serde_wasm_bindgen::Serializer::new())?;
+    Ok(output.unchecked_into())
+}
+
+/// Parse the config like the Node binding does, where `null` and `undefined` mean the default.
+fn config_from_value(mut value: Value) -> Result<Config, JsError> {
+    if let Value::Object(config) = &mut value {
+        config.retain(|_, value| !value.is_null());
+        for options in ["parser", "js2svg"] {
+            if let Some(Value::Object(options)) = config.get_mut(options) {
+                options.retain(|_, value| !value.is_null());
+            }
+        }
+    }
+    if value.is_null() {
+        return Ok(Config::default());
+    }
+    Config::deserialize(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn nulls_are_defaults() {
+        let config = config_from_value(json!({
+            "multipass": null,
+            "floatPrecision": 2,
+            "js2svg": { "pretty": true, "eol": null },
+            "plugins": ["removeComments"],
+        }))
+        .unwrap();
+        assert!(!config.multipass);
+        assert_eq!(config.float_precision, Some(2));
+        assert!(config.js2svg.pretty);
+        assert_eq!(config.plugins.len(), 1);
+        assert!(config_from_value(Value::Null).is_ok());
+    }
+
+    /// The fields of the interfaces declared in TypeScript, without the comments.
+    fn interfaces(ts: &str) -> Vec<(&str, Vec<&str>)> {
+        let mut interfaces = vec![];
+        let mut lines = ts.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            let Some(name) = line.strip_prefix("export interface ").and_then(|line| line.strip_suffix(" {")) else {
+                continue;
+            };
+            let fields = lines
+                .by_ref()
+                .take_while(|line| *line != "}")
+                .filter(|line| !line.starts_with("/*") && !line.starts_with('*'))
+                .collect();
+            interfaces.push((name, fields));
+        }
+        interfaces
+    }
+
+    #[test]
+    fn ts_types_match_the_node_binding() {
+        let index = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../../index.d.ts")).unwrap();
+        let node = interfaces(&index);
+        for (name, fields) in interfaces(TYPES) {
+            let (_, node_fields) = node
+                .iter()
+                .find(|(node_name, _)| *node_name == name)
+                .unwrap_or_else(|| panic!("no interface {} in index.d.ts", name));
+            // custom plugins can only run in Node
+            let node_fields: Vec<&str> = node_fields.iter().copied().filter(|field| !field.starts_with("fn?:")).collect();
+            assert_eq!(fields, node_fields, "interface {}", name);
+        }
+    }
+}
@@ -7,8 +7,101 @@ export interface TransformOutput {
   code: string
   map?: string
 }
+/** A problem which did not stop the optimization. */
+export interface Warning {
+  message: string
+  /** The plugin reporting the problem, none for the parser. */
+  plugin?: string
+  /** Path of the SVG file, from the `path` of the config. */
+  path?: string
+  /** 1-based line of the problem, if known. */
+  line?: number
+  /** 1-based column of the problem, if known. */
+  column?: number
+  /** The source line of the problem with a caret under the column, if known. */
+  snippet?: string
+}
 export interface Output {
   data: string
+  /** Errors the parser recovered from and problems reported by the plugins. */
+  warnings: Array<Warning>
+  /** How many times the plugins were run. */
+  passes: number
+  /** Size of the input in bytes. */
+  originalSize: number
+  /** Size of `data` in bytes. */
+  optimizedSize: number
+  /** Stats of every plugin, in the order they ran. */
+  plugins: Array<PluginStats>
+}
+/** What a plugin did, summed over all the passes. */
+export interface PluginStats {
+  name: string
+  /** Time spent in the plugin, in milliseconds. */
+  time: number
+  /**
+   * How many bytes the plugin removed from the output, negative when it added some.
+   *
+   * Only measured with the `stats` option, as it stringifies the document after every plugin.
+   */
+  savedBytes?: number
+}
+/** Options of the output. */
+export interface Js2SvgOptions {
+  indent?: number
+  pretty?: boolean
+  useShortTags?: boolean
+  eol?: 'lf' | 'crlf'
+  finalNewline?: boolean
+}
+/** How the parser treats whitespace. */
+export interface ParseOptions {
+  /** Keep the text in `xml:space="preserve"` subtrees, `true` by default. */
+  preserveXmlSpace?: boolean
+  /** Keep the whitespace of `<style>`, `<script>` and `<foreignObject>`. */
+  preserveRawText?: boolean
+  /** Keep the comments as is instead of trimming them. */
+  verbatimComments?: boolean
+}
+/** A plugin of the config with its params, e.g. `{ name: 'preset-default', params: { overrides: {} } }`. */
+export interface PluginItem {
+  name: string
+  params?: Record<string, any>
+  /** A custom plugin returning an SVGO visitor, only supported by `optimize`. */
+  fn?: (root: any, params: any, info: any) => any
+}
+/** The SVGO config. */
+export interface Config {
+  /** Path of the SVG file, used in error messages. */
+  path?: string
+  /** Rerun the plugins until the output stops shrinking. */
+  multipass?: boolean
+  /** Measure the bytes saved by every plugin, stringifying the document after each of them. */
+  stats?: boolean
+  /** Number of decimals kept by every plugin rounding numbers, unless its params set it. */
+  floatPrecision?: number
+  parser?: ParseOptions
+  js2svg?: Js2SvgOptions
+  /** Output a data URI instead of the SVG. */
+  datauri?: 'base64' | 'enc' | 'unenc'
+  /** Plugins to run, in order, `preset-default` by default. */
+  plugins?: Array<string | PluginItem>
 }
 /** The core of SVGO RS */
-export function optimize(input: string): Output
+export function optimize(input: string, config?: Config | undefined | null): Output
+/** Like `optimize`, running on the libuv thread pool instead of blocking the event loop. */
+export function optimizeAsync(input: string, config?: Config | undefined | null): Promise<Output>
+/** An SVG of `optimizeMany`, either its content or the path of its file. */
+export interface ManyInput {
+  data?: string
+  path?: string
+}
+/** The result of a single SVG of `optimizeMany`. */
+export interface ManyResult {
+  output?: Output
+  error?: string
+}
+/** Optimize many SVGs on a thread pool, resolving with the results in the order of the inputs. */
+export function optimizeMany(inputs: Array<ManyInput>, config?: Config | undefined | null): Promise<Array<ManyResult>>
+/** Returned by an `enter` callback of a custom plugin to skip the children of the node, and its `exit` callback. */
+export const visitSkip: string
@@ -252,6 +252,9 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { optimize } = nativeBinding
+const { optimize, optimizeAsync, optimizeMany, visitSkip } = nativeBinding
 
 module.exports.optimize = optimize
+module.exports.optimizeAsync = optimizeAsync
+module.exports.optimizeMany = optimizeMany
+module.exports.visitSkip = visitSkip